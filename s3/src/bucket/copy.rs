@@ -1,7 +1,14 @@
-use crate::bucket::{Bucket, Request};
+use crate::bucket::{
+    error_from_response_data, precondition_failed, Bucket, Part, PutStreamResponse, Request,
+    CHUNK_SIZE, MIN_PART_SIZE,
+};
+use crate::bucket_ops::CopyConditions;
 use crate::command::Command;
 use crate::error::S3Error;
-use crate::request::RequestImpl;
+use crate::request::{DataStream, RequestImpl, ResponseData};
+use crate::utils::object_key;
+
+use futures::StreamExt;
 
 impl Bucket {
     /// Copy file from an S3 path, internally within the same bucket.
@@ -31,24 +38,532 @@ impl Bucket {
         from: F,
         to: T,
     ) -> Result<u16, S3Error> {
-        let fq_from = {
-            let from = from.as_ref();
-            let from = from.strip_prefix('/').unwrap_or(from);
-            format!("{bucket}/{path}", bucket = self.name(), path = from)
-        };
-        self.copy_object(fq_from, to).await
+        let response_data = self
+            .copy_object(self.copy_source(from.as_ref()), to, None)
+            .await?;
+        Ok(response_data.status_code())
     }
 
-    async fn copy_object<F: AsRef<str>, T: AsRef<str>>(
+    /// Like [`Bucket::copy_object_internal`], copying only if the source object meets
+    /// `conditions`. Fails with [`S3Error::PreconditionFailed`] otherwise.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::CopyConditions;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let conditions = CopyConditions {
+    ///     if_match: Some("\"9b2cf535f27731c974343645a3985328\"".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let code = bucket
+    ///     .copy_object_internal_with_conditions("/from.file", "/to.file", &conditions)
+    ///     .await?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_object_internal_with_conditions<F: AsRef<str>, T: AsRef<str>>(
         &self,
         from: F,
         to: T,
+        conditions: &CopyConditions,
     ) -> Result<u16, S3Error> {
+        let response_data = self
+            .copy_object(self.copy_source(from.as_ref()), to, Some(conditions))
+            .await?;
+        Ok(response_data.status_code())
+    }
+
+    /// Rename an object within the bucket. S3 has no rename, so the object is copied to `to`,
+    /// with its metadata, and `from` is deleted once the copy succeeded. A failed copy leaves
+    /// `from` in place.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// bucket.rename_object("/from.file", "/to.file").await?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rename_object<F: AsRef<str>, T: AsRef<str>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<(), S3Error> {
+        let from = from.as_ref();
+        let response_data = self.copy_object(self.copy_source(from), to, None).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+
+        let request = RequestImpl::new(self, from, Command::DeleteObject)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        Ok(())
+    }
+
+    /// `x-amz-copy-source` of the object at `path` in this bucket.
+    fn copy_source(&self, path: &str) -> String {
+        format!("{}/{}", self.name(), object_key(path))
+    }
+
+    /// Copy an object from another bucket, possibly with another provider or account, by
+    /// streaming it through this process. Meant for transfers where server side copy isn't an
+    /// option.
+    ///
+    /// Like with [`Bucket::put_object_stream`], objects of at least the multipart threshold of
+    /// this bucket are uploaded with a multipart upload, part by part as they are downloaded, so
+    /// no more than the threshold, or a part, is held in memory at a time. A failed download of
+    /// the source fails with the error S3 answered it with, before anything is uploaded.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let source = Bucket::new("source-bucket", "us-east-1".parse()?, Credentials::default()?)?;
+    /// let destination = Bucket::new(
+    ///     "destination-bucket",
+    ///     "eu-central-1".parse()?,
+    ///     Credentials::from_profile(Some("other-account"))?,
+    /// )?;
+    ///
+    /// let response = destination.transfer_from(&source, "/from.file", "/to.file").await?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transfer_from(
+        &self,
+        src: &Bucket,
+        src_key: &str,
+        dest_key: &str,
+    ) -> Result<PutStreamResponse, S3Error> {
+        let mut source = src.get_object_stream(src_key).await?;
+        if source.status_code >= 300 {
+            let status_code = source.status_code;
+            let headers = source.headers();
+            let body = source.collect().await?;
+            let response_data = ResponseData::new(body, status_code, headers);
+            let (Ok(error) | Err(error)) = error_from_response_data(response_data);
+            return Err(error.for_key(src_key));
+        }
+
+        let mut pending = Vec::new();
+        fill(&mut source.bytes, &mut pending, self.multipart_threshold).await?;
+        if pending.len() < self.multipart_threshold {
+            let response_data = self.put_object(dest_key, &pending).await?;
+            return Ok(PutStreamResponse::new(
+                response_data.status_code(),
+                pending.len(),
+            ));
+        }
+        if self.multipart_threshold < MIN_PART_SIZE {
+            return Err(S3Error::MultipartThreshold(self.multipart_threshold));
+        }
+
+        let content_type = "application/octet-stream";
+        let msg = self
+            .initiate_multipart_upload(dest_key, content_type)
+            .await?;
+        let mut total_size = 0;
+        // Any failure from here on, be it reading the source, a part or the completion, aborts
+        // the upload
+        let result = async {
+            let mut parts: Vec<Part> = Vec::new();
+            // Parts are CHUNK_SIZE regardless of how much was read to reach the threshold
+            let mut part = next_part(&mut source.bytes, &mut pending).await?;
+            loop {
+                let done = part.len() < CHUNK_SIZE;
+                total_size += part.len();
+                parts.push(
+                    self.put_multipart_chunk(
+                        part,
+                        &msg.key,
+                        parts.len() as u32 + 1,
+                        &msg.upload_id,
+                        content_type,
+                    )
+                    .await?,
+                );
+                if done {
+                    break;
+                }
+                part = next_part(&mut source.bytes, &mut pending).await?;
+                // The object size is a multiple of the part size
+                if part.is_empty() {
+                    break;
+                }
+            }
+            let response_data = self
+                .complete_multipart_upload(&msg.key, &msg.upload_id, parts)
+                .await?;
+            if response_data.status_code() >= 300 {
+                return Err(error_from_response_data(response_data)?);
+            }
+            Ok(response_data)
+        }
+        .await;
+
+        match result {
+            Ok(response_data) => Ok(PutStreamResponse::new(
+                response_data.status_code(),
+                total_size,
+            )),
+            Err(error) => Err(self
+                .abort_failed_upload(&msg.key, &msg.upload_id, error)
+                .await),
+        }
+    }
+
+    async fn copy_object<F: AsRef<str>, T: AsRef<str>>(
+        &self,
+        from: F,
+        to: T,
+        conditions: Option<&CopyConditions>,
+    ) -> Result<ResponseData, S3Error> {
         let command = Command::CopyObject {
             from: from.as_ref(),
+            conditions,
         };
         let request = RequestImpl::new(self, to.as_ref(), command)?;
-        let response_data = request.response_data(false).await?;
-        Ok(response_data.status_code())
+        let response_data = precondition_failed(request.response_data(false).await)?;
+        // A copy can also fail after S3 answered 200 OK, the error is then the body
+        if response_data.status_code() == 200 && response_data.as_str_lossy().contains("<Error>") {
+            return Err(error_from_response_data(response_data)?);
+        }
+        Ok(response_data)
+    }
+}
+
+/// Read the next [`CHUNK_SIZE`] bytes from `stream`, fewer at its end. Bytes received beyond the
+/// part are kept in `pending` for the next one.
+async fn next_part(stream: &mut DataStream, pending: &mut Vec<u8>) -> Result<Vec<u8>, S3Error> {
+    fill(stream, pending, CHUNK_SIZE).await?;
+    let rest = pending.split_off(pending.len().min(CHUNK_SIZE));
+    Ok(std::mem::replace(pending, rest))
+}
+
+/// Read from `stream` into `pending` until it holds at least `size` bytes, or `stream` ends.
+async fn fill(stream: &mut DataStream, pending: &mut Vec<u8>, size: usize) -> Result<(), S3Error> {
+    while pending.len() < size {
+        match stream.next().await {
+            Some(bytes) => pending.extend_from_slice(&bytes?),
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::CHUNK_SIZE;
+    use crate::bucket_ops::CopyConditions;
+    use crate::error::S3Error;
+    use crate::test_utils::{http_response, serve_once, serve_sequence, MockS3};
+    use time::macros::datetime;
+
+    #[tokio::test]
+    async fn test_copy_object_with_conditions() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            "<CopyObjectResult><ETag>\"etag\"</ETag></CopyObjectResult>",
+        ));
+        let conditions = CopyConditions {
+            if_match: Some("\"match\"".to_string()),
+            if_none_match: Some("\"none-match\"".to_string()),
+            if_modified_since: Some(datetime!(2013-05-24 0:00 UTC)),
+            if_unmodified_since: Some(datetime!(2014-05-24 0:00 UTC)),
+        };
+        let code = bucket
+            .copy_object_internal_with_conditions("/from.file", "/to.file", &conditions)
+            .await
+            .unwrap();
+        assert_eq!(code, 200);
+
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .contains("x-amz-copy-source: rust-s3-test/from.file\r\n"));
+        assert!(request
+            .head
+            .contains("x-amz-copy-source-if-match: \"match\"\r\n"));
+        assert!(request
+            .head
+            .contains("x-amz-copy-source-if-none-match: \"none-match\"\r\n"));
+        assert!(request
+            .head
+            .contains("x-amz-copy-source-if-modified-since: fri, 24 may 2013 00:00:00 +0000\r\n"));
+        assert!(request.head.contains(
+            "x-amz-copy-source-if-unmodified-since: sat, 24 may 2014 00:00:00 +0000\r\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rename_object() {
+        let mock = MockS3::start();
+        let bucket = mock.bucket("rust-s3-test");
+        bucket.put_object("/from.file", b"content").await.unwrap();
+
+        bucket
+            .rename_object("/from.file", "/to.file")
+            .await
+            .unwrap();
+        assert_eq!(mock.keys("rust-s3-test"), ["to.file"]);
+        assert_eq!(
+            mock.object("rust-s3-test", "to.file").as_deref(),
+            Some(&b"content"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_object_failed_copy_keeps_source() {
+        // The server answers a single request, the copy, a delete would fail to connect
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[],
+            "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+        ));
+        let error = bucket
+            .rename_object("/from.file", "/to.file")
+            .await
+            .unwrap_err();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("put /rust-s3-test/to.file"));
+        assert!(request
+            .head
+            .contains("x-amz-copy-source: rust-s3-test/from.file\r\n"));
+        assert!(request.head.contains("x-amz-metadata-directive: copy\r\n"));
+        assert_eq!(error.status_code(), Some(404));
+
+        // Failed after the 200 OK
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            "<Error><Code>InternalError</Code><Message>We encountered an internal error. Please try again.</Message></Error>",
+        ));
+        let error = bucket
+            .rename_object("/from.file", "/to.file")
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.code().as_deref(), Some("InternalError"));
+    }
+
+    #[tokio::test]
+    async fn test_rename_object_failed_delete() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                "<CopyObjectResult><ETag>\"etag\"</ETag></CopyObjectResult>",
+            ),
+            http_response(
+                "403 Forbidden",
+                &[("x-amz-request-id", "request-id")],
+                "<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>",
+            ),
+        ]);
+        let error = bucket
+            .rename_object("/from.file", "/to.file")
+            .await
+            .unwrap_err();
+        let requests = server.join().unwrap();
+        assert!(requests[1]
+            .head
+            .starts_with("delete /rust-s3-test/from.file http/1.1"));
+        assert_eq!(error.status_code(), Some(403));
+        assert_eq!(error.code().as_deref(), Some("AccessDenied"));
+        assert_eq!(error.request_id(), Some("request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_object_error_after_ok() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            "<Error><Code>InternalError</Code><Message>We encountered an internal error. Please try again.</Message></Error>",
+        ));
+        let error = bucket
+            .copy_object_internal("/from.file", "/to.file")
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.code().as_deref(), Some("InternalError"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_object_precondition_failed() {
+        let (bucket, server) = serve_once(http_response(
+            "412 Precondition Failed",
+            &[],
+            "<Error><Code>PreconditionFailed</Code></Error>",
+        ));
+        let conditions = CopyConditions {
+            if_match: Some("\"stale\"".to_string()),
+            ..Default::default()
+        };
+        let error = bucket
+            .copy_object_internal_with_conditions("/from.file", "/to.file", &conditions)
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::PreconditionFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_from() {
+        let content: Vec<u8> = (0..CHUNK_SIZE + 1000).map(|i| (i % 251) as u8).collect();
+        let (source, source_server) = serve_once(http_response("200 OK", &[], &content));
+        let (destination, destination_server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                "<InitiateMultipartUploadResult><Bucket>rust-s3-test</Bucket><Key>to.file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+            ),
+            http_response("200 OK", &[("ETag", "\"etag-1\"")], ""),
+            http_response("200 OK", &[("ETag", "\"etag-2\"")], ""),
+            http_response("200 OK", &[], "<CompleteMultipartUploadResult/>"),
+        ]);
+
+        let response = destination
+            .transfer_from(&source, "/from.file", "/to.file")
+            .await
+            .unwrap();
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(response.uploaded_bytes(), content.len());
+
+        let source_request = source_server.join().unwrap();
+        assert!(source_request
+            .head
+            .starts_with("get /rust-s3-test/from.file http/1.1"));
+
+        let requests = destination_server.join().unwrap();
+        assert!(requests[0]
+            .head
+            .starts_with("post /rust-s3-test/to.file?uploads http/1.1"));
+        assert!(requests[1].head.contains("partnumber=1&uploadid=upload-id"));
+        assert!(requests[2].head.contains("partnumber=2&uploadid=upload-id"));
+        let uploaded = [requests[1].body.as_slice(), requests[2].body.as_slice()].concat();
+        assert_eq!(uploaded, content);
+        let complete = String::from_utf8(requests[3].body.clone()).unwrap();
+        assert!(complete.contains("<ETag>\"etag-2\"</ETag>"));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_from_failed_source() {
+        let (source, source_server) = serve_once(http_response(
+            "404 Not Found",
+            &[("x-amz-request-id", "request-id")],
+            "<Error><Code>NoSuchKey</Code></Error>",
+        ));
+        // Nothing is uploaded, a request to the destination would fail to connect
+        let (destination, destination_server) = serve_sequence(vec![]);
+
+        let error = destination
+            .transfer_from(&source, "/missing.file", "/to.file")
+            .await
+            .unwrap_err();
+        source_server.join().unwrap();
+        assert!(destination_server.join().unwrap().is_empty());
+        assert!(matches!(error, S3Error::NoSuchKey { ref key, .. } if key == "/missing.file"));
+        assert_eq!(error.request_id(), Some("request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_from_multipart_threshold() {
+        const THRESHOLD: usize = 6 * 1024 * 1024;
+
+        // Smaller than a part, but at the threshold of the destination
+        let content: Vec<u8> = (0..THRESHOLD + 1000).map(|i| (i % 251) as u8).collect();
+        let (source, source_server) = serve_once(http_response("200 OK", &[], &content));
+        let (destination, destination_server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                "<InitiateMultipartUploadResult><Bucket>rust-s3-test</Bucket><Key>to.file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+            ),
+            http_response("200 OK", &[("ETag", "\"etag-1\"")], ""),
+            http_response("200 OK", &[], "<CompleteMultipartUploadResult/>"),
+        ]);
+        let destination = destination.with_multipart_threshold(THRESHOLD);
+
+        let response = destination
+            .transfer_from(&source, "/from.file", "/to.file")
+            .await
+            .unwrap();
+        assert_eq!(response.uploaded_bytes(), content.len());
+        source_server.join().unwrap();
+        let requests = destination_server.join().unwrap();
+        assert!(requests[0]
+            .head
+            .starts_with("post /rust-s3-test/to.file?uploads http/1.1"));
+        assert!(requests[1].head.contains("partnumber=1&uploadid=upload-id"));
+        assert_eq!(requests[1].body, content);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_from_aborts_failed_upload() {
+        let content: Vec<u8> = (0..CHUNK_SIZE + 1000).map(|i| (i % 251) as u8).collect();
+        let (source, source_server) = serve_once(http_response("200 OK", &[], &content));
+        let (destination, destination_server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                "<InitiateMultipartUploadResult><Bucket>rust-s3-test</Bucket><Key>to.file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+            ),
+            http_response("200 OK", &[("ETag", "\"etag-1\"")], ""),
+            http_response("500 Internal Server Error", &[], "<Error><Code>InternalError</Code></Error>"),
+            http_response("204 No Content", &[], ""),
+        ]);
+
+        let error = destination
+            .transfer_from(&source, "/from.file", "/to.file")
+            .await
+            .unwrap_err();
+        // The error of the part, not of aborting
+        assert!(
+            matches!(error, S3Error::HttpFailWithBody(500, ref body, _) if body.contains("InternalError"))
+        );
+
+        source_server.join().unwrap();
+        let requests = destination_server.join().unwrap();
+        assert!(requests[2].head.contains("partnumber=2&uploadid=upload-id"));
+        assert!(requests[3]
+            .head
+            .starts_with("delete /rust-s3-test/to.file?uploadid=upload-id http/1.1"));
     }
 }
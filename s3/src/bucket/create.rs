@@ -1,18 +1,22 @@
-use crate::bucket::Bucket;
+use crate::bucket::{error_from_response_data, Bucket, BucketLocationResult};
 use crate::command::Command;
 use crate::error::S3Error;
+use crate::provider::Provider;
 use crate::request::Request;
 use crate::request::RequestImpl;
+use crate::retry::RetryPolicy;
+use crate::utils::parse_s3_url;
 use crate::BucketConfiguration;
 use awscreds::Credentials;
 use awsregion::Region;
 use http::HeaderMap;
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    sync::{atomic::AtomicI64, Arc, RwLock},
 };
+use url::Url;
 
-use super::DEFAULT_REQUEST_TIMEOUT;
+use super::{CHUNK_SIZE, DEFAULT_REQUEST_TIMEOUT};
 
 #[allow(dead_code)]
 pub struct CreateBucketResponse {
@@ -130,11 +134,157 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
             path_style: false,
             listobjects_v2: true,
+            transfer_acceleration: false,
+            dualstack: false,
+            resolver: None,
+            auto_decompress: false,
+            unsigned_payload: false,
+            multipart_threshold: CHUNK_SIZE,
+            max_response_size: None,
+            host_header: None,
+            metrics: None,
+            clock: None,
+            clock_skew_correction: false,
+            clock_offset: Arc::new(AtomicI64::new(0)),
         })
     }
 
+    /// Instantiate an existing `Bucket` hosted by `provider`, with the endpoint, addressing
+    /// style and ListObjects version the provider needs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::provider::Provider;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let credentials = Credentials::default().unwrap();
+    ///
+    /// let bucket = Bucket::for_provider(bucket_name, Provider::Gcs, credentials).unwrap();
+    /// ```
+    pub fn for_provider(
+        name: &str,
+        provider: Provider,
+        credentials: Credentials,
+    ) -> Result<Bucket, S3Error> {
+        let mut bucket = Bucket::new(name, provider.region(), credentials)?;
+        if provider.path_style() {
+            bucket.set_path_style();
+        }
+        if !provider.listobjects_v2() {
+            bucket.set_listobjects_v1();
+        }
+        Ok(bucket)
+    }
+
+    /// Instantiate an existing `Bucket` from the URL of one of its objects, or of the bucket
+    /// itself, in any of the forms understood by [`parse_s3_url`], along with the key of the
+    /// object if the URL names one.
+    ///
+    /// The bucket is in the region named by the URL, `us-east-1` if it names none, and uses
+    /// path style requests if the URL does. URLs of hosts other than AWS are taken to be path
+    /// style ones of an S3 compatible server, e.g. MinIO, which the bucket is then sent to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    ///
+    /// let credentials = Credentials::default().unwrap();
+    ///
+    /// let (bucket, key) =
+    ///     Bucket::from_url("s3://rust-s3-test/dir/test.file", credentials).unwrap();
+    /// assert_eq!(key.as_deref(), Some("dir/test.file"));
+    /// ```
+    pub fn from_url(
+        url: &str,
+        credentials: Credentials,
+    ) -> Result<(Bucket, Option<String>), S3Error> {
+        let location = parse_s3_url(url)?;
+        let mut region = location.region.unwrap_or(Region::UsEast1);
+        let mut path_style = false;
+        if !url.starts_with("s3://") {
+            let parsed = Url::parse(url)?;
+            if let Some(host) = parsed.host_str() {
+                if !host.ends_with(".amazonaws.com") && !host.ends_with(".amazonaws.com.cn") {
+                    region = Region::Custom {
+                        region: "us-east-1".to_string(),
+                        endpoint: parsed.origin().ascii_serialization(),
+                    };
+                }
+                path_style = !host.starts_with(&format!("{}.", location.bucket));
+            }
+        }
+
+        let mut bucket = Bucket::new(&location.bucket, region, credentials)?;
+        if path_style {
+            bucket.set_path_style();
+        }
+        let key = Some(location.key).filter(|key| !key.is_empty());
+        Ok((bucket, key))
+    }
+
+    /// Instantiate an existing `Bucket` in whichever region it is, found with a
+    /// GetBucketLocation sent to the global endpoint, `s3.amazonaws.com`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let bucket_name = "rust-s3-test";
+    /// let credentials = Credentials::default()?;
+    ///
+    /// let bucket = Bucket::new_autodetect(bucket_name, credentials).await?;
+    /// println!("{} is in {}", bucket_name, bucket.region());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_autodetect(name: &str, credentials: Credentials) -> Result<Bucket, S3Error> {
+        Bucket::new(name, Region::UsEast1, credentials)?
+            .with_detected_region()
+            .await
+    }
+
+    /// The bucket, moved to the region it is in.
+    async fn with_detected_region(mut self) -> Result<Bucket, S3Error> {
+        self.region = self.detect_region().await?;
+        Ok(self)
+    }
+
+    /// The region of the bucket, from its location constraint, or the `x-amz-bucket-region`
+    /// header of the response if the request was rejected for being sent to the wrong region.
+    async fn detect_region(&self) -> Result<Region, S3Error> {
+        let request = RequestImpl::new(self, "/", Command::GetBucketLocation)?;
+        let response_data = match request.response_data(false).await {
+            Ok(response_data) => response_data,
+            Err(error) => {
+                let region = error
+                    .headers()
+                    .and_then(|headers| headers.get("x-amz-bucket-region"))
+                    .and_then(|region| region.to_str().ok());
+                return match region {
+                    Some(region) => Ok(region.parse()?),
+                    None => Err(error),
+                };
+            }
+        };
+        if response_data.status_code() != 200 {
+            return match response_data.headers().get("x-amz-bucket-region") {
+                Some(region) => Ok(region.parse()?),
+                None => Err(error_from_response_data(response_data)?),
+            };
+        }
+        location_constraint_region(response_data.as_str()?)
+    }
+
     /// Instantiate a public existing `Bucket`.
     ///
     /// # Example
@@ -154,8 +304,203 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
             path_style: false,
             listobjects_v2: true,
+            transfer_acceleration: false,
+            dualstack: false,
+            resolver: None,
+            auto_decompress: false,
+            unsigned_payload: false,
+            multipart_threshold: CHUNK_SIZE,
+            max_response_size: None,
+            host_header: None,
+            metrics: None,
+            clock: None,
+            clock_skew_correction: false,
+            clock_offset: Arc::new(AtomicI64::new(0)),
         })
     }
 }
+
+/// The region of a GetBucketLocation response body. Buckets in `us-east-1` have no location
+/// constraint, and the oldest ones in `eu-west-1` have `EU`.
+fn location_constraint_region(body: &str) -> Result<Region, S3Error> {
+    let location = quick_xml::de::from_str::<BucketLocationResult>(body)?;
+    Ok(match location.region.as_str() {
+        "" => Region::UsEast1,
+        "EU" => Region::EuWest1,
+        region => region.parse()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::location_constraint_region;
+    use crate::command::Command;
+    use crate::provider::Provider;
+    use crate::request::{Request, RequestImpl};
+    use crate::test_utils::{fake_credentials, http_response, serve_once};
+    use crate::{Bucket, BucketConfiguration, Region};
+
+    async fn create_bucket_request(region_name: &str) -> crate::test_utils::ReceivedRequest {
+        let (bucket, server) = serve_once(http_response("200 OK", &[], ""));
+        let region = Region::Custom {
+            region: region_name.to_owned(),
+            endpoint: bucket.region.endpoint(),
+        };
+        Bucket::create_with_path_style(
+            "rust-s3-test",
+            region,
+            fake_credentials(),
+            BucketConfiguration::default(),
+        )
+        .await
+        .unwrap();
+        server.join().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detected_region() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<LocationConstraint xmlns="http://s3.amazonaws.com/doc/2006-03-01/">eu-west-1</LocationConstraint>"#,
+        ));
+        let bucket = bucket.with_detected_region().await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/?location http/1.1"));
+        assert_eq!(bucket.region(), Region::EuWest1);
+
+        // Requests are now signed for the detected region
+        let request = RequestImpl::new(&bucket, "/test.file", Command::GetObject).unwrap();
+        let headers = request.headers().unwrap();
+        assert!(headers["authorization"]
+            .to_str()
+            .unwrap()
+            .contains("/eu-west-1/s3/aws4_request"));
+    }
+
+    #[tokio::test]
+    async fn test_detected_region_from_header() {
+        let (bucket, server) = serve_once(http_response(
+            "301 Moved Permanently",
+            &[("x-amz-bucket-region", "ap-southeast-2")],
+            "<Error><Code>PermanentRedirect</Code></Error>",
+        ));
+        let bucket = bucket.with_detected_region().await.unwrap();
+        server.join().unwrap();
+        assert_eq!(bucket.region(), Region::ApSoutheast2);
+    }
+
+    #[test]
+    fn test_location_constraint_region() {
+        let xmlns = r#"xmlns="http://s3.amazonaws.com/doc/2006-03-01/""#;
+        for (body, region) in [
+            (
+                format!(
+                    "<LocationConstraint {}>eu-central-1</LocationConstraint>",
+                    xmlns
+                ),
+                Region::EuCentral1,
+            ),
+            (
+                format!("<LocationConstraint {}>EU</LocationConstraint>", xmlns),
+                Region::EuWest1,
+            ),
+            (
+                format!("<LocationConstraint {}></LocationConstraint>", xmlns),
+                Region::UsEast1,
+            ),
+            (format!("<LocationConstraint {}/>", xmlns), Region::UsEast1),
+        ] {
+            assert_eq!(
+                location_constraint_region(&body).unwrap(),
+                region,
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_provider() {
+        let bucket =
+            Bucket::for_provider("rust-s3-test", Provider::Gcs, fake_credentials()).unwrap();
+        assert!(!bucket.listobjects_v2);
+        assert_eq!(bucket.host(), "rust-s3-test.storage.googleapis.com");
+
+        let provider = Provider::R2 {
+            account_id: "0123456789abcdef".to_string(),
+        };
+        let bucket = Bucket::for_provider("rust-s3-test", provider, fake_credentials()).unwrap();
+        assert!(bucket.listobjects_v2);
+        assert_eq!(bucket.region().to_string(), "auto");
+        assert_eq!(
+            bucket.host(),
+            "rust-s3-test.0123456789abcdef.r2.cloudflarestorage.com"
+        );
+
+        let provider = Provider::Minio {
+            endpoint: "http://localhost:9000".to_string(),
+        };
+        let bucket = Bucket::for_provider("rust-s3-test", provider, fake_credentials()).unwrap();
+        assert!(bucket.is_path_style());
+        assert_eq!(bucket.url(), "http://localhost:9000/rust-s3-test");
+    }
+
+    #[test]
+    fn test_from_url() {
+        let (bucket, key) =
+            Bucket::from_url("s3://rust-s3-test/dir/test.file", fake_credentials()).unwrap();
+        assert_eq!(key.as_deref(), Some("dir/test.file"));
+        assert_eq!(bucket.region(), Region::UsEast1);
+        assert_eq!(bucket.host(), "rust-s3-test.s3.amazonaws.com");
+
+        let (bucket, key) = Bucket::from_url(
+            "https://rust-s3-test.s3.eu-west-1.amazonaws.com/test.file",
+            fake_credentials(),
+        )
+        .unwrap();
+        assert_eq!(key.as_deref(), Some("test.file"));
+        assert_eq!(bucket.region(), Region::EuWest1);
+        assert!(!bucket.is_path_style());
+        assert_eq!(bucket.host(), "rust-s3-test.s3-eu-west-1.amazonaws.com");
+
+        let (bucket, key) = Bucket::from_url(
+            "https://s3.eu-central-1.amazonaws.com/rust-s3-test",
+            fake_credentials(),
+        )
+        .unwrap();
+        assert_eq!(key, None);
+        assert_eq!(bucket.region(), Region::EuCentral1);
+        assert!(bucket.is_path_style());
+        assert_eq!(bucket.host(), "s3.eu-central-1.amazonaws.com");
+
+        let (bucket, key) =
+            Bucket::from_url("http://localhost:9000/rust-s3-test/a/b", fake_credentials()).unwrap();
+        assert_eq!(key.as_deref(), Some("a/b"));
+        assert!(bucket.is_path_style());
+        assert_eq!(bucket.region().to_string(), "us-east-1");
+        assert_eq!(bucket.url(), "http://localhost:9000/rust-s3-test");
+
+        assert!(Bucket::from_url("rust-s3-test/test.file", fake_credentials()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_bucket_location_constraint() {
+        let request = create_bucket_request("us-east-1").await;
+        assert!(request.head.starts_with("put /rust-s3-test"));
+        assert!(request.head.contains("content-length: 0"));
+        assert!(request.body.is_empty());
+
+        let request = create_bucket_request("eu-central-1").await;
+        assert_eq!(
+            String::from_utf8(request.body).unwrap(),
+            "<CreateBucketConfiguration><LocationConstraint>eu-central-1</LocationConstraint></CreateBucketConfiguration>"
+        );
+    }
+}
@@ -1,8 +1,10 @@
-use crate::bucket::Bucket;
-use crate::command::Command;
+use crate::bucket::{error_from_response_data, precondition_failed, Bucket, DeleteObjectOutput};
+use crate::command::{Command, ConfigurationKind};
 use crate::error::S3Error;
 use crate::request::RequestImpl;
 use crate::request::{Request, ResponseData};
+use crate::serde_types::{DeleteObjectsRequest, DeleteObjectsResult, ObjectIdentifier};
+use crate::utils::object_key;
 
 impl Bucket {
     /// Delete existing `Bucket`
@@ -49,15 +51,155 @@ impl Bucket {
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     ///
-    /// let response_data = bucket.delete_object("/test.file").await?;
+    /// let output = bucket.delete_object("/test.file").await?;
+    /// if output.delete_marker() {
+    ///     println!("Created delete marker {:?}", output.version_id());
+    /// }
     ///
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn delete_object<S: AsRef<str>>(&self, path: S) -> Result<ResponseData, S3Error> {
+    pub async fn delete_object<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<DeleteObjectOutput, S3Error> {
         let command = Command::DeleteObject;
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data(false).await
+        let response_data = request.response_data(false).await?;
+        Ok(DeleteObjectOutput::from_response_data(&response_data))
+    }
+
+    /// Delete file from an S3 path, only if its ETag is still `etag`, so an object that was
+    /// changed in the meantime isn't deleted. Fails with [`S3Error::PreconditionFailed`]
+    /// otherwise.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::error::S3Error;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let (head_object_result, _) = bucket.head_object("/test.file").await?;
+    /// let etag = head_object_result.e_tag.unwrap_or_default();
+    /// match bucket.delete_object_if_match("/test.file", &etag).await {
+    ///     Ok(_) => println!("Deleted"),
+    ///     Err(S3Error::PreconditionFailed { .. }) => println!("Changed in the meantime"),
+    ///     Err(error) => return Err(error.into()),
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_object_if_match<S: AsRef<str>>(
+        &self,
+        path: S,
+        etag: &str,
+    ) -> Result<DeleteObjectOutput, S3Error> {
+        let command = Command::DeleteObjectIfMatch { etag };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let response_data = precondition_failed(request.response_data(false).await)?;
+        Ok(DeleteObjectOutput::from_response_data(&response_data))
+    }
+
+    /// Delete up to 1000 objects in a single request.
+    ///
+    /// Objects that couldn't be deleted don't fail the request, they are listed in the
+    /// [`DeleteObjectsResult::errors`] instead.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let result = bucket.delete_objects(&["/a.file", "/b.file"]).await?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_objects<S: AsRef<str>>(
+        &self,
+        keys: &[S],
+    ) -> Result<DeleteObjectsResult, S3Error> {
+        let objects = keys
+            .iter()
+            .map(|key| ObjectIdentifier::new(key.as_ref()))
+            .collect();
+        self.delete_objects_versioned(objects, false).await
+    }
+
+    /// Delete up to 1000 objects in a single request, each either its current version or the
+    /// version given in its [`ObjectIdentifier`]. With `quiet`, only the objects that couldn't
+    /// be deleted are reported.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::{Bucket, ObjectIdentifier};
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let objects = vec![
+    ///     ObjectIdentifier::new("a.file"),
+    ///     ObjectIdentifier::with_version("b.file", "3HL4kqtJlcpXroDTDmjVBH40Nrjfkd"),
+    /// ];
+    /// let result = bucket.delete_objects_versioned(objects, true).await?;
+    /// for error in result.errors {
+    ///     println!("Could not delete {}: {}", error.key, error.code);
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_objects_versioned(
+        &self,
+        objects: Vec<ObjectIdentifier>,
+        quiet: bool,
+    ) -> Result<DeleteObjectsResult, S3Error> {
+        // Keys are relative to the bucket, like paths given to the other methods
+        let objects = objects
+            .into_iter()
+            .map(|object| ObjectIdentifier {
+                key: object_key(&object.key).to_string(),
+                ..object
+            })
+            .collect();
+        let command = Command::DeleteObjects {
+            delete: DeleteObjectsRequest { quiet, objects },
+        };
+        let request = RequestImpl::new(self, "/", command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
     }
 
     /// Delete tags from an S3 object.
@@ -90,4 +232,309 @@ impl Bucket {
         let request = RequestImpl::new(self, path.as_ref(), command)?;
         request.response_data(false).await
     }
+
+    /// Remove the default server-side encryption configuration of the bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let response_data = bucket.delete_bucket_encryption().await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_bucket_encryption(&self) -> Result<ResponseData, S3Error> {
+        let request = RequestImpl::new(self, "/", Command::DeleteBucketEncryption)?;
+        request.response_data(false).await
+    }
+
+    /// Delete the inventory configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// bucket.delete_bucket_inventory_configuration("weekly-report").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_bucket_inventory_configuration(
+        &self,
+        id: &str,
+    ) -> Result<ResponseData, S3Error> {
+        let command = Command::DeleteBucketConfiguration {
+            kind: ConfigurationKind::Inventory,
+            id,
+        };
+        let request = RequestImpl::new(self, "/", command)?;
+        request.response_data(false).await
+    }
+
+    /// Delete the storage class analysis configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// bucket.delete_bucket_analytics_configuration("whole-bucket").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_bucket_analytics_configuration(
+        &self,
+        id: &str,
+    ) -> Result<ResponseData, S3Error> {
+        let command = Command::DeleteBucketConfiguration {
+            kind: ConfigurationKind::Analytics,
+            id,
+        };
+        let request = RequestImpl::new(self, "/", command)?;
+        request.response_data(false).await
+    }
+
+    /// Delete the request metrics configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// bucket.delete_bucket_metrics_configuration("whole-bucket").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_bucket_metrics_configuration(
+        &self,
+        id: &str,
+    ) -> Result<ResponseData, S3Error> {
+        let command = Command::DeleteBucketConfiguration {
+            kind: ConfigurationKind::Metrics,
+            id,
+        };
+        let request = RequestImpl::new(self, "/", command)?;
+        request.response_data(false).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::S3Error;
+    use crate::serde_types::ObjectIdentifier;
+    use crate::test_utils::{http_response, serve_once};
+
+    #[tokio::test]
+    async fn test_delete_object_delete_marker() {
+        let (bucket, server) = serve_once(http_response(
+            "204 No Content",
+            &[
+                ("x-amz-delete-marker", "true"),
+                (
+                    "x-amz-version-id",
+                    "UIORUnfndfiufdisojhr398493jfdkjFJjkndnqUifhnw89493jJFJ",
+                ),
+            ],
+            "",
+        ));
+        let output = bucket.delete_object("/test.file").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(output.status_code(), 204);
+        assert!(output.delete_marker());
+        assert_eq!(
+            output.version_id(),
+            Some("UIORUnfndfiufdisojhr398493jfdkjFJjkndnqUifhnw89493jJFJ")
+        );
+
+        // Unversioned buckets send neither header
+        let (bucket, server) = serve_once(http_response("204 No Content", &[], ""));
+        let output = bucket.delete_object("/test.file").await.unwrap();
+        server.join().unwrap();
+        assert!(!output.delete_marker());
+        assert_eq!(output.version_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_object_if_match() {
+        let (bucket, server) = serve_once(http_response("204 No Content", &[], ""));
+        let output = bucket
+            .delete_object_if_match("/test.file", "\"etag\"")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("delete /rust-s3-test/test.file"));
+        assert!(request.head.contains("if-match: \"etag\"\r\n"));
+        assert_eq!(output.status_code(), 204);
+
+        let (bucket, server) = serve_once(http_response(
+            "412 Precondition Failed",
+            &[("ETag", "\"changed\"")],
+            "<Error><Code>PreconditionFailed</Code><Message>At least one of the pre-conditions you specified did not hold</Message></Error>",
+        ));
+        let error = bucket
+            .delete_object_if_match("/test.file", "\"etag\"")
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(
+            matches!(error, S3Error::PreconditionFailed { etag: Some(ref etag), .. } if etag == "\"changed\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_objects_partial_failure() {
+        // Verbose, the outcome of every key, in the order they were processed
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Deleted><Key>a.file</Key></Deleted>
+  <Error><Key>b.file</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>
+  <Deleted><Key>c.file</Key><DeleteMarker>true</DeleteMarker><DeleteMarkerVersionId>m1</DeleteMarkerVersionId></Deleted>
+  <Error><Key>d.file</Key><Code>InternalError</Code><Message>We encountered an internal error. Please try again.</Message></Error>
+</DeleteResult>"#,
+        ));
+        let result = bucket
+            .delete_objects(&["/a.file", "/b.file", "/c.file", "/d.file"])
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(!String::from_utf8(request.body).unwrap().contains("<Quiet>"));
+        assert_eq!(
+            result
+                .deleted
+                .iter()
+                .map(|deleted| deleted.key.as_str())
+                .collect::<Vec<_>>(),
+            ["a.file", "c.file"]
+        );
+        assert!(result.deleted[1].delete_marker);
+        assert_eq!(
+            result.deleted[1].delete_marker_version_id.as_deref(),
+            Some("m1")
+        );
+        assert_eq!(
+            result
+                .errors
+                .iter()
+                .map(|error| (error.key.as_str(), error.code.as_str()))
+                .collect::<Vec<_>>(),
+            [("b.file", "AccessDenied"), ("d.file", "InternalError")]
+        );
+
+        // Quiet, only the errors
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Error><Key>b.file</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>
+</DeleteResult>"#,
+        ));
+        let objects = vec![
+            ObjectIdentifier::new("a.file"),
+            ObjectIdentifier::new("b.file"),
+        ];
+        let result = bucket
+            .delete_objects_versioned(objects, true)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert!(result.deleted.is_empty());
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].message.as_deref(), Some("Access Denied"));
+
+        // Quiet and everything deleted, nothing to report
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/"/>"#,
+        ));
+        let objects = vec![ObjectIdentifier::new("a.file")];
+        let result = bucket
+            .delete_objects_versioned(objects, true)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert!(result.deleted.is_empty());
+        assert!(result.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_objects_versioned() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            "<DeleteResult><Error><Key>b.file</Key><VersionId>v1</VersionId><Code>AccessDenied</Code><Message>Access Denied</Message></Error></DeleteResult>",
+        ));
+
+        let objects = vec![
+            ObjectIdentifier::new("/a.file"),
+            ObjectIdentifier::with_version("b.file", "v1"),
+        ];
+        let result = bucket
+            .delete_objects_versioned(objects, true)
+            .await
+            .unwrap();
+        assert!(result.deleted.is_empty());
+        assert_eq!(result.errors[0].key, "b.file");
+        assert_eq!(result.errors[0].version_id.as_deref(), Some("v1"));
+
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("post /rust-s3-test/?delete http/1.1"));
+        assert!(request.head.contains("content-md5: "));
+        assert_eq!(
+            String::from_utf8(request.body).unwrap(),
+            "<Delete><Quiet>true</Quiet><Object><Key>a.file</Key></Object><Object><Key>b.file</Key><VersionId>v1</VersionId></Object></Delete>"
+        );
+    }
 }
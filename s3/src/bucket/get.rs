@@ -1,14 +1,81 @@
-use crate::bucket::{Bucket, Request};
-use crate::command::Command;
+use crate::bucket::head::existing_object;
+use crate::bucket::{
+    error_from_response_data, AccelerateConfiguration, AccessControlPolicy, AnalyticsConfiguration,
+    Bucket, HeadObjectResult, InventoryConfiguration, MetricsConfiguration,
+    NotificationConfiguration, OwnershipControls, Request, ServerSideEncryptionConfiguration,
+};
+use crate::bucket_ops::GetObjectOptions;
+use crate::checksum::ChecksumAlgorithm;
+use crate::command::{Command, ConfigurationKind};
 use crate::error::S3Error;
 use crate::request::RequestImpl;
 use crate::request::ResponseData;
+use crate::utils::{parse_content_range, precondition_failed};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use std::convert::TryFrom;
+use std::path::Path;
 
-use crate::request::{AsyncWrite, ResponseDataStream};
+use crate::request::{AsyncRead, AsyncWrite, ResponseDataStream, Stream};
+
+/// Split a `multipart/byteranges` body into its parts, each with the first and last byte
+/// positions of its `Content-Range`. `None` if the body is malformed. The parts are taken by
+/// the length of their range rather than by looking for the next boundary, which could just
+/// as well appear in binary content.
+fn split_byteranges(boundary: &str, body: &Bytes) -> Option<Vec<(u64, u64, Bytes)>> {
+    let delimiter = format!("--{}", boundary);
+    let find = |from: usize, needle: &[u8]| {
+        body.get(from..)?
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .map(|position| from + position)
+    };
+
+    let mut parts = Vec::new();
+    let mut position = find(0, delimiter.as_bytes())?;
+    loop {
+        position += delimiter.len();
+        if body.get(position..position + 2)? == b"--" {
+            return Some(parts);
+        }
+        let headers_end = find(position, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&body[position..headers_end]).ok()?;
+        let (first, last) = headers.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if name.trim().eq_ignore_ascii_case("content-range") {
+                parse_content_range(value.trim())
+            } else {
+                None
+            }
+        })?;
+        if last < first {
+            return None;
+        }
+        let start = headers_end + 4;
+        let stop = start.checked_add(usize::try_from(last - first + 1).ok()?)?;
+        if stop > body.len() {
+            return None;
+        }
+        parts.push((first, last, body.slice(start..stop)));
+        position = find(stop, delimiter.as_bytes())?;
+    }
+}
+
+/// The bytes of the inclusive range `start`-`end` out of `parts` of an object, each with its
+/// first and last byte positions. The end of the range is clamped to the part holding it.
+fn range_from_parts(parts: &[(u64, u64, Bytes)], start: u64, end: Option<u64>) -> Option<Bytes> {
+    let (first, last, bytes) = parts
+        .iter()
+        .find(|(first, last, _)| *first <= start && start <= *last)?;
+    let stop = end.map_or(*last, |end| end.min(*last));
+    Some(bytes.slice((start - first) as usize..=(stop - first) as usize))
+}
 
 impl Bucket {
     /// Gets file from an S3 path.
     ///
+    /// With the `fail-on-err` feature, a missing object fails with [`S3Error::NoSuchKey`].
+    ///
     /// # Example:
     ///
     /// ```rust,no_run
@@ -31,7 +98,183 @@ impl Bucket {
     pub async fn get_object<S: AsRef<str>>(&self, path: S) -> Result<ResponseData, S3Error> {
         let command = Command::GetObject;
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data(false).await
+        request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
+    }
+
+    /// Gets file from an S3 path, with S3 answering `response_content_type` as its
+    /// `Content-Type` instead of the stored one, e.g. for objects uploaded with the wrong type.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let response_data = bucket.get_object_as("/report.csv", "text/csv").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_as<S: AsRef<str>>(
+        &self,
+        path: S,
+        response_content_type: &str,
+    ) -> Result<ResponseData, S3Error> {
+        let command = Command::GetObjectAs {
+            response_content_type,
+        };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
+    }
+
+    /// Gets file from an S3 path, sending the request headers of `options`.
+    ///
+    /// With [`GetObjectOptions::checksum_mode`], a whole object is checked against the checksum
+    /// S3 stored for it, if any, failing with [`S3Error::ChecksumMismatch`] if it doesn't match.
+    /// Ranges, objects decompressed on the fly and the checksums of objects uploaded in parts
+    /// can't be checked.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::GetObjectOptions;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let options = GetObjectOptions {
+    ///     cache_control: Some("max-age=60".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let response_data = bucket.get_object_with_options("/test.file", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_with_options<S: AsRef<str>>(
+        &self,
+        path: S,
+        options: &GetObjectOptions,
+    ) -> Result<ResponseData, S3Error> {
+        let command = Command::GetObjectWithOptions { options };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let response_data = request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))?;
+
+        let decompressed =
+            self.is_auto_decompress() && response_data.header("content-encoding").is_some();
+        if options.checksum_mode && response_data.status_code() == 200 && !decompressed {
+            for algorithm in ChecksumAlgorithm::ALL {
+                if response_data.verify_checksum(*algorithm) == Some(false) {
+                    return Err(S3Error::ChecksumMismatch {
+                        algorithm: *algorithm,
+                        expected: response_data.checksum(*algorithm).unwrap_or("").to_string(),
+                        computed: algorithm.compute(response_data.as_slice()),
+                    });
+                }
+            }
+        }
+        Ok(response_data)
+    }
+
+    /// Gets file from an S3 path with `Cache-Control: no-cache`, so caches in front of the
+    /// bucket, like CloudFront, fetch it from the bucket again instead of serving a stored copy.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let response_data = bucket.get_object_fresh("/test.file").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_fresh<S: AsRef<str>>(&self, path: S) -> Result<ResponseData, S3Error> {
+        let options = GetObjectOptions {
+            cache_control: Some("no-cache".to_string()),
+            ..Default::default()
+        };
+        self.get_object_with_options(path, &options).await
+    }
+
+    /// Gets file from an S3 path only if `predicate` accepts its metadata, from a HEAD sent
+    /// first, e.g. to skip objects of a size already known, `None` if it doesn't.
+    ///
+    /// The download is conditioned on the ETag the HEAD returned, so it fails with
+    /// [`S3Error::PreconditionFailed`] if the object was replaced in between. A missing object
+    /// fails with [`S3Error::NoSuchKey`].
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let local_size = 1024;
+    /// let response_data = bucket
+    ///     .get_object_if("/test.file", |head| head.content_length != Some(local_size))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_if<S: AsRef<str>>(
+        &self,
+        path: S,
+        predicate: impl FnOnce(&HeadObjectResult) -> bool,
+    ) -> Result<Option<ResponseData>, S3Error> {
+        let path = path.as_ref();
+        let (head_object_result, status) = self.head_object(path).await?;
+        let head_object_result = existing_object(path, head_object_result, status)?;
+        if !predicate(&head_object_result) {
+            return Ok(None);
+        }
+        let options = GetObjectOptions {
+            if_match: head_object_result.e_tag,
+            ..Default::default()
+        };
+        precondition_failed(self.get_object_with_options(path, &options).await).map(Some)
     }
 
     /// Gets torrent from an S3 path.
@@ -98,7 +341,257 @@ impl Bucket {
 
         let command = Command::GetObjectRange { start, end };
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data(false).await
+        request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
+    }
+
+    /// Gets specified inclusive byte range of file from an S3 path, returning just the bytes of
+    /// that range.
+    ///
+    /// Unlike [`Bucket::get_object_range`], the returned range is checked against the
+    /// requested one. Some servers ignore `Range` and answer `200` with the whole object; with
+    /// `slice_full_body` the range is then cut out client side, otherwise this fails with
+    /// [`S3Error::RangeNotHonored`], as it does for partial responses covering a different
+    /// range than requested.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let bytes = bucket.get_object_range_bytes("/test.file", 0, Some(31), true).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_range_bytes<S: AsRef<str>>(
+        &self,
+        path: S,
+        start: u64,
+        end: Option<u64>,
+        slice_full_body: bool,
+    ) -> Result<Bytes, S3Error> {
+        let response_data = self.get_object_range(path, start, end).await?;
+        let body = response_data.bytes().clone();
+        match response_data.status_code() {
+            206 => {
+                let content_range = response_data.headers().get("content-range").cloned();
+                let honored = match content_range.as_deref().and_then(parse_content_range) {
+                    // The end of the range is clamped to the size of the object
+                    Some((first, last)) => {
+                        first == start
+                            && !matches!(end, Some(end) if last > end)
+                            && last.checked_sub(first).map(|len| len.saturating_add(1))
+                                == Some(body.len() as u64)
+                    }
+                    None => !matches!(
+                        end,
+                        Some(end) if body.len() as u64 > end.saturating_sub(start).saturating_add(1)
+                    ),
+                };
+                if !honored {
+                    return Err(S3Error::RangeNotHonored(format!(
+                        "requested bytes={}-{}, got {} bytes with Content-Range {:?}",
+                        start,
+                        end.map(|end| end.to_string()).unwrap_or_default(),
+                        body.len(),
+                        content_range
+                    )));
+                }
+                Ok(body)
+            }
+            200 if slice_full_body => {
+                let len = body.len() as u64;
+                if start >= len {
+                    return Err(S3Error::RangeNotHonored(format!(
+                        "range starts at {} but the object is only {} bytes",
+                        start, len
+                    )));
+                }
+                let stop = end.map_or(len, |end| end.saturating_add(1).min(len));
+                Ok(body.slice(start as usize..stop as usize))
+            }
+            200 => Err(S3Error::RangeNotHonored(format!(
+                "got the full object of {} bytes instead of a partial response",
+                body.len()
+            ))),
+            _ => Err(error_from_response_data(response_data)?),
+        }
+    }
+
+    /// Gets several inclusive byte ranges of file from an S3 path in a single request, e.g. for
+    /// sparse reads, returning the bytes of each range in the order they were requested.
+    ///
+    /// The ranges are sent in one `Range` header, `bytes=0-99,200-299`, which servers
+    /// supporting it answer with a `multipart/byteranges` body. S3 itself doesn't, it answers
+    /// with the whole object, out of which the ranges are then cut client side. A partial
+    /// response missing one of the ranges fails with [`S3Error::RangeNotHonored`]. No ranges
+    /// at all, or one ending before it starts, fail with [`S3Error::InvalidRange`] without
+    /// sending a request.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let ranges = bucket
+    ///     .get_object_ranges("/test.file", &[(0, Some(99)), (200, Some(299))])
+    ///     .await?;
+    /// assert_eq!(ranges.len(), 2);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_ranges<S: AsRef<str>>(
+        &self,
+        path: S,
+        ranges: &[(u64, Option<u64>)],
+    ) -> Result<Vec<Bytes>, S3Error> {
+        if ranges.is_empty() {
+            return Err(S3Error::InvalidRange("no ranges requested".to_string()));
+        }
+        for &(start, end) in ranges {
+            if let Some(end) = end {
+                if start > end {
+                    return Err(S3Error::InvalidRange(format!(
+                        "bytes={}-{} ends before it starts",
+                        start, end
+                    )));
+                }
+            }
+        }
+
+        let command = Command::GetObjectRanges { ranges };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let response_data = request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))?;
+        let body = response_data.bytes().clone();
+        let parts = match response_data.status_code() {
+            206 => {
+                let headers = response_data.headers();
+                let boundary = headers.get("content-type").and_then(|content_type| {
+                    let (media_type, parameters) = content_type.split_once(';')?;
+                    if !media_type
+                        .trim()
+                        .eq_ignore_ascii_case("multipart/byteranges")
+                    {
+                        return None;
+                    }
+                    parameters.split(';').find_map(|parameter| {
+                        let (name, value) = parameter.split_once('=')?;
+                        name.trim()
+                            .eq_ignore_ascii_case("boundary")
+                            .then(|| value.trim().trim_matches('"').to_string())
+                    })
+                });
+                match boundary {
+                    Some(boundary) => split_byteranges(&boundary, &body).ok_or_else(|| {
+                        S3Error::RangeNotHonored("malformed multipart/byteranges body".to_string())
+                    })?,
+                    // A single range, e.g. the requested ones merged, is sent as is
+                    None => {
+                        let content_range = headers.get("content-range");
+                        match content_range
+                            .map(String::as_str)
+                            .and_then(parse_content_range)
+                        {
+                            Some((first, last)) if body.len() as u64 == last - first + 1 => {
+                                vec![(first, last, body)]
+                            }
+                            _ => {
+                                return Err(S3Error::RangeNotHonored(format!(
+                                    "got {} bytes with Content-Range {:?}",
+                                    body.len(),
+                                    content_range
+                                )))
+                            }
+                        }
+                    }
+                }
+            }
+            200 if body.is_empty() => Vec::new(),
+            200 => vec![(0, body.len() as u64 - 1, body)],
+            _ => return Err(error_from_response_data(response_data)?),
+        };
+
+        ranges
+            .iter()
+            .map(|&(start, end)| {
+                range_from_parts(&parts, start, end).ok_or_else(|| {
+                    S3Error::RangeNotHonored(format!(
+                        "requested bytes={}-{} is missing from the response",
+                        start,
+                        end.map(|end| end.to_string()).unwrap_or_default()
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Gets a single part of an object uploaded with multipart upload, along with the number of
+    /// parts the object consists of (from `x-amz-mp-parts-count`). Part numbers start at 1;
+    /// objects uploaded in one piece have a single part.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let (response_data, parts_count) = bucket.get_object_part("/test.file", 1).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_part<S: AsRef<str>>(
+        &self,
+        path: S,
+        part_number: u32,
+    ) -> Result<(ResponseData, Option<u32>), S3Error> {
+        let command = Command::GetObjectPart { part_number };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let response_data = request
+            .response_data(false)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))?;
+        let parts_count = response_data
+            .headers()
+            .get("x-amz-mp-parts-count")
+            .and_then(|count| count.parse().ok());
+        Ok((response_data, parts_count))
     }
 
     /// Stream range of bytes from S3 path to a local file, generic over T: Write.
@@ -120,8 +613,6 @@ impl Bucket {
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     /// let mut output_file = File::create("output_file").expect("Unable to create file");
     /// let mut async_output_file = tokio::fs::File::create("async_output_file").await.expect("Unable to create file");
-    /// #[cfg(feature = "with-async-std")]
-    /// let mut async_output_file = async_std::fs::File::create("async_output_file").await.expect("Unable to create file");
     ///
     /// let start = 0;
     /// let end = Some(1024);
@@ -144,7 +635,10 @@ impl Bucket {
 
         let command = Command::GetObjectRange { start, end };
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data_to_writer(writer).await
+        request
+            .response_data_to_writer(writer)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
     }
 
     /// Stream file from S3 path to a local file, generic over T: Write.
@@ -166,8 +660,6 @@ impl Bucket {
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     /// let mut output_file = File::create("output_file").expect("Unable to create file");
     /// let mut async_output_file = tokio::fs::File::create("async_output_file").await.expect("Unable to create file");
-    /// #[cfg(feature = "with-async-std")]
-    /// let mut async_output_file = async_std::fs::File::create("async_output_file").await.expect("Unable to create file");
     ///
     /// let status_code = bucket.get_object_to_writer("/test.file", &mut async_output_file).await?;
     /// #
@@ -181,19 +673,26 @@ impl Bucket {
     ) -> Result<u16, S3Error> {
         let command = Command::GetObject;
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data_to_writer(writer).await
+        request
+            .response_data_to_writer(writer)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
     }
 
-    /// Stream file from S3 path to a local file using an async stream.
+    /// Download the object at an S3 path to `local_path`, replacing the file if it exists.
     ///
-    /// # Example
+    /// The body goes to a temporary file next to `local_path`, allocated to the object's
+    /// `Content-Length` up front and written frame by frame as it arrives, without being
+    /// buffered in memory. It replaces `local_path` once complete, so a failed request or
+    /// download leaves an existing file untouched. Fails with an IO error if the body ends
+    /// short of its `Content-Length`.
+    ///
+    /// # Example:
     ///
     /// ```rust,no_run
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
-    /// use tokio_stream::StreamExt;
-    /// use tokio::io::AsyncWriteExt;
     ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<()> {
@@ -202,34 +701,37 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
-    /// let path = "path";
-    ///
-    /// let mut response_data_stream = bucket.get_object_stream(path).await?;
-    ///
-    /// let mut async_output_file = tokio::fs::File::create("async_output_file").await.expect("Unable to create file");
-    ///
-    /// while let Some(chunk) = response_data_stream.bytes().next().await {
-    ///     async_output_file.write_all(&chunk.unwrap()).await?;
-    /// }
     ///
+    /// let status_code = bucket.get_object_to_file("/test.file", "output_file").await?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_object_stream<S: AsRef<str>>(
+    pub async fn get_object_to_file<S: AsRef<str>, P: AsRef<Path>>(
         &self,
         path: S,
-    ) -> Result<ResponseDataStream, S3Error> {
+        local_path: P,
+    ) -> Result<u16, S3Error> {
         let command = Command::GetObject;
         let request = RequestImpl::new(self, path.as_ref(), command)?;
-        request.response_data_to_stream().await
+        request
+            .response_data_to_file(local_path.as_ref(), None)
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
     }
 
-    /// Retrieve an S3 object list of tags.
+    /// Download a range of bytes of the object at an S3 path into `local_path`, at the same
+    /// offset as in the object. The file is created if needed, but never truncated, so
+    /// ranges can be downloaded concurrently into the same file.
+    ///
+    /// Nothing is written, nor the file created, unless the server answers with the requested
+    /// range: a `200` with the whole object, or a partial response starting elsewhere, fails
+    /// with [`S3Error::RangeNotHonored`]. A range ending before it starts fails with
+    /// [`S3Error::InvalidRange`].
     ///
     /// # Example:
     ///
-    /// ```no_run
+    /// ```rust,no_run
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
@@ -242,27 +744,196 @@ impl Bucket {
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     ///
-    /// let response_data = bucket.get_object_tagging("/test.file").await?;
+    /// let size = bucket.object_size("/test.file").await?;
+    /// let half = size / 2;
+    /// let (first, second) = futures::future::join(
+    ///     bucket.get_object_range_to_file("/test.file", 0, Some(half - 1), "output_file"),
+    ///     bucket.get_object_range_to_file("/test.file", half, None, "output_file"),
+    /// )
+    /// .await;
+    /// first?;
+    /// second?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_object_tagging<S: AsRef<str>>(
+    pub async fn get_object_range_to_file<S: AsRef<str>, P: AsRef<Path>>(
         &self,
         path: S,
-    ) -> Result<(Vec<crate::Tag>, u16), S3Error> {
-        let command = Command::GetObjectTagging {};
-        let request = RequestImpl::new(self, path.as_ref(), command)?;
-        let result = request.response_data(false).await?;
-
-        let mut tags = Vec::new();
+        start: u64,
+        end: Option<u64>,
+        local_path: P,
+    ) -> Result<u16, S3Error> {
+        if let Some(end) = end {
+            if start > end {
+                return Err(S3Error::InvalidRange(format!(
+                    "bytes={}-{} ends before it starts",
+                    start, end
+                )));
+            }
+        }
 
-        if result.status_code() == 200 {
-            let result_string = String::from_utf8_lossy(result.as_slice());
+        let command = Command::GetObjectRange { start, end };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        request
+            .response_data_to_file(local_path.as_ref(), Some(start))
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
+    }
 
-            // Add namespace if it doesn't exist
-            let ns = "http://s3.amazonaws.com/doc/2006-03-01/";
-            let result_string = if let Err(minidom::Error::MissingNamespace) =
+    /// Stream file from S3 path to a local file using an async stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use tokio_stream::StreamExt;
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let path = "path";
+    ///
+    /// let mut response_data_stream = bucket.get_object_stream(path).await?;
+    ///
+    /// let mut async_output_file = tokio::fs::File::create("async_output_file").await.expect("Unable to create file");
+    ///
+    /// while let Some(chunk) = response_data_stream.bytes().next().await {
+    ///     async_output_file.write_all(&chunk.unwrap()).await?;
+    /// }
+    ///
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_stream<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<ResponseDataStream, S3Error> {
+        let command = Command::GetObject;
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        request
+            .response_data_to_stream()
+            .await
+            .map_err(|e| e.for_key(path.as_ref()))
+    }
+
+    /// Read the object at an S3 path through an [`AsyncRead`], without loading it into
+    /// memory, e.g. to pass it to [`tokio::io::copy`]. Failures while reading the body surface
+    /// as [`std::io::Error`]s, see [`ResponseDataStream::into_reader`].
+    ///
+    /// With the `fail-on-err` feature, a missing object fails with [`S3Error::NoSuchKey`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut reader = bucket.get_object_reader("/test.file").await?;
+    /// let mut async_output_file = tokio::fs::File::create("async_output_file").await?;
+    /// tokio::io::copy(&mut reader, &mut async_output_file).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_reader<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<impl AsyncRead + Send + Unpin, S3Error> {
+        Ok(self.get_object_stream(path).await?.into_reader())
+    }
+
+    /// Stream the lines of a text object at an S3 path, e.g. CSV or JSON Lines, without loading
+    /// it into memory. See [`ResponseDataStream::into_lines`].
+    ///
+    /// With the `fail-on-err` feature, a missing object fails with [`S3Error::NoSuchKey`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use futures::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut lines = bucket.get_object_lines("/events.jsonl").await?;
+    /// while let Some(line) = lines.next().await {
+    ///     println!("{}", line?);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_lines<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<impl Stream<Item = Result<String, S3Error>> + Send + Unpin, S3Error> {
+        Ok(Box::pin(self.get_object_stream(path).await?.into_lines()))
+    }
+
+    /// Retrieve an S3 object list of tags.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let response_data = bucket.get_object_tagging("/test.file").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_tagging<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<(Vec<crate::Tag>, u16), S3Error> {
+        let command = Command::GetObjectTagging {};
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let result = request.response_data(false).await?;
+
+        let mut tags = Vec::new();
+
+        if result.status_code() == 200 {
+            let result_string = String::from_utf8_lossy(result.as_slice());
+
+            // Add namespace if it doesn't exist
+            let ns = "http://s3.amazonaws.com/doc/2006-03-01/";
+            let result_string = if let Err(minidom::Error::MissingNamespace) =
                 result_string.parse::<minidom::Element>()
             {
                 result_string
@@ -297,4 +968,1090 @@ impl Bucket {
 
         Ok((tags, result.status_code()))
     }
+
+    /// Get the default server-side encryption of new objects in the bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let configuration = bucket.get_bucket_encryption().await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_encryption(
+        &self,
+    ) -> Result<ServerSideEncryptionConfiguration, S3Error> {
+        let request = RequestImpl::new(self, "/", Command::GetBucketEncryption)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Get the Transfer Acceleration state of the bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let enabled = bucket.get_bucket_accelerate_configuration().await?.is_enabled();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_accelerate_configuration(
+        &self,
+    ) -> Result<AccelerateConfiguration, S3Error> {
+        let bucket = self.clone().with_transfer_acceleration(false);
+        let command = Command::GetBucketAccelerateConfiguration;
+        let request = RequestImpl::new(&bucket, "/", command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Get the access control policy of the bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let policy = bucket.get_bucket_acl().await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_acl(&self) -> Result<AccessControlPolicy, S3Error> {
+        let request = RequestImpl::new(self, "/", Command::GetBucketAcl)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Get who owns the objects uploaded to the bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let object_ownership = bucket.get_bucket_ownership_controls().await?.object_ownership();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_ownership_controls(&self) -> Result<OwnershipControls, S3Error> {
+        let command = Command::GetBucketOwnershipControls;
+        let request = RequestImpl::new(self, "/", command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Get where events of the bucket are published.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let configuration = bucket.get_bucket_notification_configuration().await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_notification_configuration(
+        &self,
+    ) -> Result<NotificationConfiguration, S3Error> {
+        let request = RequestImpl::new(self, "/", Command::GetBucketNotificationConfiguration)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Get the inventory configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let configuration = bucket.get_bucket_inventory_configuration("weekly-report").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_inventory_configuration(
+        &self,
+        id: &str,
+    ) -> Result<InventoryConfiguration, S3Error> {
+        self.get_bucket_configuration(ConfigurationKind::Inventory, id)
+            .await
+    }
+
+    /// Get the storage class analysis configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let configuration = bucket.get_bucket_analytics_configuration("whole-bucket").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_analytics_configuration(
+        &self,
+        id: &str,
+    ) -> Result<AnalyticsConfiguration, S3Error> {
+        self.get_bucket_configuration(ConfigurationKind::Analytics, id)
+            .await
+    }
+
+    /// Get the request metrics configuration with the given `id`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let configuration = bucket.get_bucket_metrics_configuration("whole-bucket").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bucket_metrics_configuration(
+        &self,
+        id: &str,
+    ) -> Result<MetricsConfiguration, S3Error> {
+        self.get_bucket_configuration(ConfigurationKind::Metrics, id)
+            .await
+    }
+
+    async fn get_bucket_configuration<T: DeserializeOwned>(
+        &self,
+        kind: ConfigurationKind,
+        id: &str,
+    ) -> Result<T, S3Error> {
+        let command = Command::GetBucketConfiguration { kind, id };
+        let request = RequestImpl::new(self, "/", command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_byteranges;
+    use crate::bucket::{
+        InventoryConfiguration, InventoryFormat, InventoryFrequency, ObjectOwnership,
+        OwnershipControls,
+    };
+    use crate::bucket_ops::GetObjectOptions;
+    use crate::checksum::ChecksumAlgorithm;
+    use crate::command::{Command, ConfigurationKind};
+    use crate::error::S3Error;
+    use crate::request::{Request, RequestImpl};
+    use crate::test_utils::{http_response, serve_once, serve_sequence, MockS3};
+    use bytes::Bytes;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_get_object_fresh() {
+        let (bucket, server) = serve_once(http_response("200 OK", &[], "content"));
+
+        let response_data = bucket.get_object_fresh("/test.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.contains("cache-control: no-cache\r\n"));
+        assert!(request.head.contains("cache-control;"));
+        assert_eq!(response_data.as_slice(), b"content");
+    }
+
+    #[tokio::test]
+    async fn test_get_object_if() {
+        let s3 = MockS3::start();
+        let bucket = s3.bucket("rust-s3-test");
+        bucket.put_object("/test.file", b"content").await.unwrap();
+
+        let response_data = bucket
+            .get_object_if("/test.file", |head| head.content_length == Some(7))
+            .await
+            .unwrap();
+        assert_eq!(response_data.unwrap().as_slice(), b"content");
+
+        let response_data = bucket
+            .get_object_if("/test.file", |head| head.content_length != Some(7))
+            .await
+            .unwrap();
+        assert!(response_data.is_none());
+
+        let error = bucket
+            .get_object_if("/missing.file", |_| true)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::NoSuchKey { .. }));
+
+        assert_eq!(
+            s3.requests()[1..],
+            [
+                "HEAD /rust-s3-test/test.file",
+                "GET /rust-s3-test/test.file",
+                "HEAD /rust-s3-test/test.file",
+                "HEAD /rust-s3-test/missing.file",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_object_if_replaced() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response("200 OK", &[("ETag", "\"old\"")], ""),
+            http_response(
+                "412 Precondition Failed",
+                &[("ETag", "\"new\"")],
+                "<Error><Code>PreconditionFailed</Code></Error>",
+            ),
+        ]);
+
+        let error = bucket
+            .get_object_if("/test.file", |_| true)
+            .await
+            .unwrap_err();
+        let requests = server.join().unwrap();
+        assert!(requests[1].head.contains("if-match: \"old\"\r\n"));
+        assert!(matches!(error, S3Error::PreconditionFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_checksum_mode() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[("x-amz-checksum-crc32", "DUoRhQ==")],
+                "hello world",
+            ),
+            http_response(
+                "200 OK",
+                &[("x-amz-checksum-crc32", "DUoRhQ==")],
+                "hello there",
+            ),
+            http_response(
+                "206 Partial Content",
+                &[("Content-Range", "bytes 0-4/11")],
+                "hello",
+            ),
+        ]);
+        let options = GetObjectOptions {
+            checksum_mode: true,
+            ..Default::default()
+        };
+
+        let response_data = bucket
+            .get_object_with_options("/test.file", &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            response_data.checksum(ChecksumAlgorithm::Crc32),
+            Some("DUoRhQ==")
+        );
+        assert_eq!(
+            response_data.verify_checksum(ChecksumAlgorithm::Crc32),
+            Some(true)
+        );
+
+        let error = bucket
+            .get_object_with_options("/test.file", &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::ChecksumMismatch { .. }));
+
+        let options = GetObjectOptions {
+            range: Some((0, Some(4))),
+            ..options
+        };
+        let response_data = bucket
+            .get_object_with_options("/test.file", &options)
+            .await
+            .unwrap();
+        assert_eq!(response_data.as_slice(), b"hello");
+        assert_eq!(
+            response_data.verify_checksum(ChecksumAlgorithm::Crc32),
+            None
+        );
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .head
+            .contains("x-amz-checksum-mode: enabled\r\n"));
+        assert!(requests[2]
+            .head
+            .contains("x-amz-checksum-mode: enabled\r\n"));
+        assert!(requests[2].head.contains("range: bytes=0-4\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_as() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[("Content-Type", "text/csv")],
+            "a,b,c",
+        ));
+
+        let response_data = bucket
+            .get_object_as("/report.csv", "text/csv")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/report.csv?response-content-type=text%2fcsv http/1.1"));
+        assert_eq!(response_data.headers()["content-type"], "text/csv");
+        assert_eq!(response_data.as_slice(), b"a,b,c");
+
+        // The override is part of the signature
+        let command = Command::GetObjectAs {
+            response_content_type: "text/csv",
+        };
+        let request = RequestImpl::new(&bucket, "/report.csv", command).unwrap();
+        let headers = request.headers().unwrap();
+        let canonical_request = request.canonical_request(&headers).unwrap();
+        assert_eq!(
+            canonical_request.lines().nth(2),
+            Some("response-content-type=text%2Fcsv")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_object_stream_headers() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[("Content-Type", "text/plain"), ("ETag", "\"etag\"")],
+            "streamed",
+        ));
+
+        let mut stream = bucket.get_object_stream("/test.file").await.unwrap();
+        let mut body = Vec::new();
+        while let Some(chunk) = stream.bytes().next().await {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        server.join().unwrap();
+
+        assert_eq!(body, b"streamed");
+        assert_eq!(stream.status_code(), 200);
+        assert_eq!(stream.header("Content-Length"), Some("8"));
+        assert_eq!(stream.header("content-type"), Some("text/plain"));
+        assert_eq!(
+            stream.headers().get("etag").map(String::as_str),
+            Some("\"etag\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bucket_ownership_controls_roundtrip() {
+        let controls = OwnershipControls::new(ObjectOwnership::BucketOwnerPreferred);
+        let (bucket, server) = serve_once(http_response("200 OK", &[], ""));
+        bucket
+            .put_bucket_ownership_controls(controls.clone())
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("put /rust-s3-test/?ownershipcontrols http/1.1"));
+        assert!(request.head.contains("content-md5: "));
+
+        // Answer the GET with what the PUT sent
+        let body = String::from_utf8(request.body).unwrap();
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &body));
+        let fetched = bucket.get_bucket_ownership_controls().await.unwrap();
+        server.join().unwrap();
+        assert_eq!(fetched, controls);
+    }
+
+    #[tokio::test]
+    async fn test_get_object_to_file() {
+        let content: Vec<u8> = (0..3 * 1024 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &content));
+        let local_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        // Replaced, not written over
+        std::fs::write(&local_path, vec![1; 4 * 1024 * 1024]).unwrap();
+
+        let status_code = bucket
+            .get_object_to_file("/large.file", &local_path)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/large.file http/1.1"));
+        assert_eq!(status_code, 200);
+        let downloaded = std::fs::read(&local_path).unwrap();
+        std::fs::remove_file(&local_path).unwrap();
+        assert!(downloaded == content);
+    }
+
+    #[tokio::test]
+    async fn test_get_object_to_file_failure_keeps_file() {
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[],
+            "<Error><Code>NoSuchKey</Code></Error>",
+        ));
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir(&dir).unwrap();
+        let local_path = dir.join("existing.file");
+        std::fs::write(&local_path, b"keep me").unwrap();
+
+        let error = bucket
+            .get_object_to_file("/missing.file", &local_path)
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.status_code(), Some(404));
+        assert_eq!(std::fs::read(&local_path).unwrap(), b"keep me");
+        // Nothing left behind next to it either
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_to_file() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "206 Partial Content",
+                &[("Content-Range", "bytes 6-10/11")],
+                "world",
+            ),
+            http_response(
+                "206 Partial Content",
+                &[("Content-Range", "bytes 0-5/11")],
+                "hello ",
+            ),
+        ]);
+        let local_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        // The second half first, the file is grown to fit it
+        bucket
+            .get_object_range_to_file("/test.file", 6, Some(10), &local_path)
+            .await
+            .unwrap();
+        bucket
+            .get_object_range_to_file("/test.file", 0, Some(5), &local_path)
+            .await
+            .unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.contains("range: bytes=6-10\r\n"));
+        assert!(requests[1].head.contains("range: bytes=0-5\r\n"));
+        let downloaded = std::fs::read(&local_path).unwrap();
+        std::fs::remove_file(&local_path).unwrap();
+        assert_eq!(downloaded, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_to_file_range_not_honored() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response("200 OK", &[], "hello world"),
+            http_response(
+                "206 Partial Content",
+                &[("Content-Range", "bytes 0-4/11")],
+                "hello",
+            ),
+        ]);
+        let local_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::write(&local_path, b"0123456789").unwrap();
+
+        // The whole object, or a different range, is never written at the requested offset
+        let error = bucket
+            .get_object_range_to_file("/test.file", 6, Some(10), &local_path)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::RangeNotHonored(_)));
+        let error = bucket
+            .get_object_range_to_file("/test.file", 6, Some(10), &local_path)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::RangeNotHonored(_)));
+        server.join().unwrap();
+        let contents = std::fs::read(&local_path).unwrap();
+        std::fs::remove_file(&local_path).unwrap();
+        assert_eq!(contents, b"0123456789");
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_to_file_failure_creates_nothing() {
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[],
+            "<Error><Code>NoSuchKey</Code></Error>",
+        ));
+        let local_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let error = bucket
+            .get_object_range_to_file("/missing.file", 0, Some(4), &local_path)
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.status_code(), Some(404));
+        assert!(!local_path.exists());
+
+        // A range ending before it starts isn't even sent
+        let error = bucket
+            .get_object_range_to_file("/test.file", 5, Some(4), &local_path)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::InvalidRange(_)));
+        assert!(!local_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_get_object_reader() {
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &content));
+
+        let mut reader = bucket.get_object_reader("/test.file").await.unwrap();
+        let mut read = Vec::new();
+        tokio::io::copy(&mut reader, &mut read).await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/test.file http/1.1"));
+        assert!(read == content);
+    }
+
+    #[tokio::test]
+    async fn test_get_object_max_response_size() {
+        let body = "x".repeat(2048);
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &body));
+        let bucket = bucket.with_max_response_size(Some(1024));
+        let error = bucket.get_object("/big.file").await.unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::ResponseTooLarge { limit: 1024 }));
+
+        // Without a Content-Length, the body is cut off once it exceeds the limit
+        let (bucket, server) = serve_once(
+            format!(
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n400\r\n{0}\r\n400\r\n{0}\r\n0\r\n\r\n",
+                "x".repeat(1024)
+            )
+            .into_bytes(),
+        );
+        let bucket = bucket.with_max_response_size(Some(1500));
+        let error = bucket.get_object("/big.file").await.unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::ResponseTooLarge { limit: 1500 }));
+
+        // Up to the limit is fine
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &body));
+        let bucket = bucket.with_max_response_size(Some(2048));
+        let response_data = bucket.get_object("/big.file").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(response_data.bytes().len(), 2048);
+
+        // Streaming isn't limited
+        let (bucket, server) = serve_once(http_response("200 OK", &[], &body));
+        let bucket = bucket.with_max_response_size(Some(1024));
+        let mut writer = Vec::new();
+        let status = bucket
+            .get_object_to_writer("/big.file", &mut writer)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(writer.len(), 2048);
+    }
+
+    #[tokio::test]
+    async fn test_get_object_lines() {
+        // The second line is split across two chunks, the last one has no newline
+        let (bucket, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+              e\r\n{\"id\":1}\n{\"id\"\r\n\
+              c\r\n:2}\n{\"id\":3}\r\n\
+              0\r\n\r\n"
+                .to_vec(),
+        );
+        let lines = bucket
+            .get_object_lines("/events.jsonl")
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("get /rust-s3-test/events.jsonl"));
+        assert_eq!(
+            lines.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            [r#"{"id":1}"#, r#"{"id":2}"#, r#"{"id":3}"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_object_part() {
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[("x-amz-mp-parts-count", "3")],
+            "second part",
+        ));
+
+        let (response_data, parts_count) =
+            bucket.get_object_part("/multipart.file", 2).await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/multipart.file?partnumber=2 http/1.1"));
+        assert_eq!(response_data.status_code(), 206);
+        assert_eq!(response_data.as_slice(), b"second part");
+        assert_eq!(parts_count, Some(3));
+
+        // The part number is part of the signature
+        let command = Command::GetObjectPart { part_number: 2 };
+        let request = RequestImpl::new(&bucket, "/multipart.file", command).unwrap();
+        let headers = request.headers().unwrap();
+        let canonical_request = request.canonical_request(&headers).unwrap();
+        assert_eq!(canonical_request.lines().nth(2), Some("partNumber=2"));
+    }
+
+    #[cfg(feature = "fail-on-err")]
+    #[tokio::test]
+    async fn test_get_object_missing_key_is_no_such_key() {
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[
+                ("Content-Type", "application/xml"),
+                ("x-amz-request-id", "4442587FB7D0A2F9"),
+                ("x-amz-id-2", "vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4="),
+            ],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Error>
+                <Code>NoSuchKey</Code>
+                <Message>The specified key does not exist.</Message>
+                <Key>missing.file</Key>
+                <RequestId>4442587FB7D0A2F9</RequestId>
+            </Error>"#,
+        ));
+
+        let error = bucket.get_object("/missing.file").await.unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(
+            error.extended_request_id(),
+            Some("vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=")
+        );
+        match error {
+            S3Error::NoSuchKey { key, .. } => assert_eq!(key, "/missing.file"),
+            error => panic!("expected NoSuchKey, got {:?}", error),
+        }
+    }
+
+    #[cfg(feature = "fail-on-err")]
+    #[tokio::test]
+    async fn test_get_object_delete_marker() {
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[("x-amz-delete-marker", "true")],
+            "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+        ));
+        let error = bucket.get_object("/deleted.file").await.unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(
+            error,
+            S3Error::NoSuchKey {
+                delete_marker: true,
+                ..
+            }
+        ));
+        assert!(error.is_delete_marker());
+
+        // Asking for the delete marker's version
+        let (bucket, server) = serve_once(http_response(
+            "405 Method Not Allowed",
+            &[("x-amz-delete-marker", "true")],
+            "<Error><Code>MethodNotAllowed</Code><Message>The specified method is not allowed against this resource.</Message></Error>",
+        ));
+        let error = bucket.get_object("/deleted.file").await.unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.status_code(), Some(405));
+        assert!(error.is_delete_marker());
+    }
+
+    #[cfg(feature = "fail-on-err")]
+    #[tokio::test]
+    async fn test_get_object_other_404_is_left_alone() {
+        let (bucket, server) = serve_once(http_response(
+            "404 Not Found",
+            &[("Content-Type", "application/xml")],
+            "<Error><Code>NoSuchBucket</Code><Message></Message></Error>",
+        ));
+
+        let error = bucket.get_object("/missing.file").await.unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.status_code(), Some(404));
+        assert_eq!(error.code().as_deref(), Some("NoSuchBucket"));
+        assert!(matches!(error, S3Error::HttpFailWithBody(..)));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_auto_decompress() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let content = "I want to go to S3 ".repeat(100);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let response = http_response("200 OK", &[("Content-Encoding", "gzip")], &gzipped);
+
+        let (bucket, server) = serve_once(response.clone());
+        let response_data = bucket
+            .with_auto_decompress(true)
+            .get_object("/test.file.gz")
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(response_data.as_slice(), content.as_bytes());
+        assert_eq!(response_data.headers()["content-encoding"], "gzip");
+
+        let (bucket, server) = serve_once(response.clone());
+        let mut writer = Vec::new();
+        bucket
+            .with_auto_decompress(true)
+            .get_object_to_writer("/test.file.gz", &mut writer)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(writer, content.as_bytes());
+
+        // Left as stored unless asked for
+        let (bucket, server) = serve_once(response);
+        let response_data = bucket.get_object("/test.file.gz").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(response_data.as_slice(), gzipped.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_bytes() {
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[("Content-Range", "bytes 2-5/10")],
+            "2345",
+        ));
+        let bytes = bucket
+            .get_object_range_bytes("/test.file", 2, Some(5), false)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.contains("range: bytes=2-5"));
+        assert_eq!(&bytes[..], b"2345");
+
+        // A partial response for a different range is rejected
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[("Content-Range", "bytes 0-3/10")],
+            "0123",
+        ));
+        let error = bucket
+            .get_object_range_bytes("/test.file", 2, Some(5), false)
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::RangeNotHonored(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_bytes_range_ignored() {
+        let response = http_response("200 OK", &[], "0123456789");
+
+        let (bucket, server) = serve_once(response.clone());
+        let bytes = bucket
+            .get_object_range_bytes("/test.file", 2, Some(5), true)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(&bytes[..], b"2345");
+
+        let (bucket, server) = serve_once(response);
+        let error = bucket
+            .get_object_range_bytes("/test.file", 2, Some(5), false)
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::RangeNotHonored(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_range_bytes_open_ended_max() {
+        // An end of u64::MAX is as good as none, for partial and full responses alike
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "206 Partial Content",
+                &[("Content-Range", "bytes 0-9/10")],
+                "0123456789",
+            ),
+            http_response("206 Partial Content", &[], "0123456789"),
+            http_response("200 OK", &[], "0123456789"),
+        ]);
+        for _ in 0..2 {
+            let bytes = bucket
+                .get_object_range_bytes("/test.file", 0, Some(u64::MAX), false)
+                .await
+                .unwrap();
+            assert_eq!(&bytes[..], b"0123456789");
+        }
+        let bytes = bucket
+            .get_object_range_bytes("/test.file", 2, Some(u64::MAX), true)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"23456789");
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_object_ranges_empty() {
+        let (bucket, server) = serve_sequence(vec![]);
+        let error = bucket
+            .get_object_ranges("/test.file", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::InvalidRange(_)));
+        // Rejected before sending anything
+        assert!(server.join().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_object_ranges_inverted() {
+        let (bucket, server) = serve_sequence(vec![]);
+        let error = bucket
+            .get_object_ranges("/test.file", &[(0, Some(3)), (8, Some(5))])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, S3Error::InvalidRange(_)));
+        assert!(server.join().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_object_ranges() {
+        let body = "--3d6b6a416f9b5\r\n\
+                    Content-Type: application/octet-stream\r\n\
+                    Content-Range: bytes 0-3/20\r\n\
+                    \r\n\
+                    0123\r\n\
+                    --3d6b6a416f9b5\r\n\
+                    Content-Type: application/octet-stream\r\n\
+                    content-range: bytes 10-19/20\r\n\
+                    \r\n\
+                    --3d6b6a41\r\n\
+                    --3d6b6a416f9b5--\r\n";
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[(
+                "Content-Type",
+                "multipart/byteranges; boundary=\"3d6b6a416f9b5\"",
+            )],
+            body,
+        ));
+        let ranges = bucket
+            .get_object_ranges("/test.file", &[(10, None), (0, Some(3)), (12, Some(15))])
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.contains("range: bytes=10-,0-3,12-15\r\n"));
+        // Content that looks like the boundary is left alone
+        assert_eq!(ranges, [&b"--3d6b6a41"[..], b"0123", b"3d6b"]);
+
+        // Ranges merged into a single one
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[("Content-Range", "bytes 2-7/10")],
+            "234567",
+        ));
+        let ranges = bucket
+            .get_object_ranges("/test.file", &[(2, Some(3)), (6, Some(7))])
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(ranges, [&b"23"[..], b"67"]);
+
+        // Ranges ignored, cut out of the whole object
+        let (bucket, server) = serve_once(http_response("200 OK", &[], "0123456789"));
+        let ranges = bucket
+            .get_object_ranges("/test.file", &[(2, Some(3)), (8, None)])
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(ranges, [&b"23"[..], b"89"]);
+
+        // A range missing from the response
+        let (bucket, server) = serve_once(http_response(
+            "206 Partial Content",
+            &[("Content-Range", "bytes 2-3/10")],
+            "23",
+        ));
+        let error = bucket
+            .get_object_ranges("/test.file", &[(2, Some(3)), (6, Some(7))])
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::RangeNotHonored(_)));
+    }
+
+    #[test]
+    fn test_split_byteranges_malformed() {
+        let body = |body: &'static str| Bytes::from_static(body.as_bytes());
+        // Shorter than its Content-Range
+        assert_eq!(
+            split_byteranges(
+                "b",
+                &body("--b\r\nContent-Range: bytes 0-9/20\r\n\r\n0123\r\n--b--\r\n")
+            ),
+            None
+        );
+        // Without a Content-Range
+        assert_eq!(
+            split_byteranges(
+                "b",
+                &body("--b\r\nContent-Type: text/plain\r\n\r\n0123\r\n--b--\r\n")
+            ),
+            None
+        );
+        // Without the closing delimiter
+        assert_eq!(
+            split_byteranges(
+                "b",
+                &body("--b\r\nContent-Range: bytes 0-3/20\r\n\r\n0123\r\n")
+            ),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_bucket_inventory_configuration() {
+        let configuration = InventoryConfiguration::new(
+            "weekly report",
+            "arn:aws:s3:::inventory-reports",
+            InventoryFormat::Csv,
+            InventoryFrequency::Weekly,
+        );
+        let (bucket, server) = serve_once(http_response("200 OK", &[], configuration.to_string()));
+
+        let fetched = bucket
+            .get_bucket_inventory_configuration("weekly report")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/?inventory&id=weekly+report http/1.1"));
+        assert_eq!(fetched, configuration);
+
+        // Both the subresource and the id are signed
+        let command = Command::GetBucketConfiguration {
+            kind: ConfigurationKind::Inventory,
+            id: "weekly report",
+        };
+        let request = RequestImpl::new(&bucket, "/", command).unwrap();
+        let headers = request.headers().unwrap();
+        let canonical_request = request.canonical_request(&headers).unwrap();
+        assert_eq!(
+            canonical_request.lines().nth(2),
+            Some("id=weekly%20report&inventory=")
+        );
+    }
 }
@@ -1,10 +1,15 @@
 use crate::bucket::*;
+use crate::checksum::ChecksumAlgorithm;
 use crate::command::Command;
 use crate::request::RequestImpl;
+use time::OffsetDateTime;
 
 impl Bucket {
     /// Head object from S3.
     ///
+    /// A missing object is not an error, even with the `fail-on-err` feature: the returned
+    /// status code is `404` instead.
+    ///
     /// # Example:
     ///
     /// ```no_run
@@ -35,4 +40,857 @@ impl Bucket {
         let header_object = HeadObjectResult::from(&headers);
         Ok((header_object, status))
     }
+
+    /// Head object from S3, `None` if it is missing. Other failures are errors.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// match bucket.stat("/test.png").await? {
+    ///     Some(head_object_result) => println!("{:?} bytes", head_object_result.content_length),
+    ///     None => println!("missing"),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stat<S: AsRef<str>>(&self, path: S) -> Result<Option<HeadObjectResult>, S3Error> {
+        let path = path.as_ref();
+        let (head_object_result, status) = self.head_object(path).await?;
+        match status {
+            404 => Ok(None),
+            status => existing_object(path, head_object_result, status).map(Some),
+        }
+    }
+
+    /// Head a specific version of an object from S3. The version is echoed back in
+    /// [`HeadObjectResult::version_id`].
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let (head_object_result, code) = bucket
+    ///     .head_object_version("/test.png", "3HL4kqtJlcpXroDTDmJ.rmSpXd3dIbrHY")
+    ///     .await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn head_object_version<S: AsRef<str>>(
+        &self,
+        path: S,
+        version_id: &str,
+    ) -> Result<(HeadObjectResult, u16), S3Error> {
+        let command = Command::HeadObjectVersion { version_id };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let (headers, status) = request.response_header().await?;
+        let header_object = HeadObjectResult::from(&headers);
+        Ok((header_object, status))
+    }
+
+    /// Head object from S3 with the query parameters `queries` among the signed ones, e.g.
+    /// `versionId`, or the overrides a GET of the same object was presigned with using
+    /// [`Bucket::presign_get`], so both see the same resource.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use std::collections::HashMap;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut queries = HashMap::new();
+    /// queries.insert("versionId".to_string(), "3HL4kqtJlcpXroDTDmJ.rmSpXd3dIbrHY".to_string());
+    /// queries.insert("response-content-type".to_string(), "text/csv".to_string());
+    /// let url = bucket.presign_get("/report.csv", 86400, Some(queries.clone()))?;
+    /// let (head_object_result, code) = bucket.head_object_with_query("/report.csv", &queries).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn head_object_with_query<S: AsRef<str>>(
+        &self,
+        path: S,
+        queries: &HashMap<String, String>,
+    ) -> Result<(HeadObjectResult, u16), S3Error> {
+        let command = Command::HeadObjectWithQuery { queries };
+        let request = RequestImpl::new(self, path.as_ref(), command)?;
+        let (headers, status) = request.response_header().await?;
+        let header_object = HeadObjectResult::from(&headers);
+        Ok((header_object, status))
+    }
+
+    /// Head object from S3 unless it is unchanged since `datetime`, sending `If-Modified-Since`,
+    /// e.g. for a cache to check whether its copy is stale with a single request.
+    ///
+    /// Fails with [`S3Error::NoSuchKey`] if there is no such object.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::{Bucket, HeadObjectModified};
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use time::OffsetDateTime;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let cached_at = OffsetDateTime::now_utc();
+    /// match bucket.head_object_if_modified_since("/test.png", cached_at).await? {
+    ///     HeadObjectModified::Modified(head_object_result) => {
+    ///         println!("Stale, now {:?}", head_object_result.e_tag)
+    ///     }
+    ///     HeadObjectModified::NotModified => println!("Still fresh"),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn head_object_if_modified_since<S: AsRef<str>>(
+        &self,
+        path: S,
+        datetime: OffsetDateTime,
+    ) -> Result<HeadObjectModified, S3Error> {
+        let path = path.as_ref();
+        let command = Command::HeadObjectIfModifiedSince { since: datetime };
+        let request = RequestImpl::new(self, path, command)?;
+        let (headers, status) = request.response_header().await?;
+        if status == 304 {
+            return Ok(HeadObjectModified::NotModified);
+        }
+        existing_object(path, HeadObjectResult::from(&headers), status)
+            .map(HeadObjectModified::Modified)
+    }
+
+    /// Get the `attributes` of an object without its content.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::{Bucket, ObjectAttribute};
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let attributes = bucket
+    ///     .get_object_attributes("/test.png", &[ObjectAttribute::ETag, ObjectAttribute::ObjectSize])
+    ///     .await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_object_attributes<S: AsRef<str>>(
+        &self,
+        path: S,
+        attributes: &[ObjectAttribute],
+    ) -> Result<GetObjectAttributesOutput, S3Error> {
+        self.get_object_attributes_page(path.as_ref(), attributes, None)
+            .await
+    }
+
+    async fn get_object_attributes_page(
+        &self,
+        path: &str,
+        attributes: &[ObjectAttribute],
+        part_number_marker: Option<u32>,
+    ) -> Result<GetObjectAttributesOutput, S3Error> {
+        let command = Command::GetObjectAttributes {
+            attributes,
+            part_number_marker,
+        };
+        let request = RequestImpl::new(self, path, command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+
+    /// Size of an object in bytes, e.g. to preallocate a buffer or show progress. Taken from
+    /// the `Content-Length` of a HEAD, or from GetObjectAttributes for providers leaving it out.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let size = bucket.object_size("/test.png").await?;
+    /// let mut buffer: Vec<u8> = Vec::with_capacity(size as usize);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn object_size<S: AsRef<str>>(&self, path: S) -> Result<u64, S3Error> {
+        let path = path.as_ref();
+        let head_object_result = self.head_existing_object(path).await?;
+        if let Some(content_length) = head_object_result.content_length {
+            return Ok(content_length as u64);
+        }
+
+        self.get_object_attributes(path, &[ObjectAttribute::ObjectSize])
+            .await?
+            .object_size
+            .ok_or_else(|| S3Error::UnknownObjectSize(path.to_string()))
+    }
+
+    /// Head an object along with the size of every part it was uploaded in, e.g. to download
+    /// it in parallel along the original part boundaries.
+    ///
+    /// The parts are listed with GetObjectAttributes, following up on the HEAD only for
+    /// objects that report a `x-amz-mp-parts-count`, so the list is empty for objects uploaded
+    /// with a single PutObject. S3 only lists the parts of objects uploaded with an additional
+    /// checksum, for others the list is empty even though
+    /// [`parts_count`](HeadObjectResult::parts_count) is set. Fails with
+    /// [`S3Error::NoSuchKey`] if there is no such object.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let (head_object_result, parts) = bucket.head_object_with_parts("/test.file").await?;
+    /// let mut start = 0;
+    /// for part in parts {
+    ///     let range = (start, start + part.size - 1);
+    ///     start += part.size;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn head_object_with_parts<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<(HeadObjectResult, Vec<ObjectPart>), S3Error> {
+        let path = path.as_ref();
+        let head_object_result = self.head_existing_object(path).await?;
+        if head_object_result.parts_count.is_none() {
+            return Ok((head_object_result, Vec::new()));
+        }
+
+        let attributes = self
+            .get_object_attributes_page(path, &[ObjectAttribute::ObjectParts], None)
+            .await?;
+        let parts = self
+            .object_parts_from(path, attributes.object_parts)
+            .await?;
+        Ok((head_object_result, parts))
+    }
+
+    /// Verify the additional checksum of an object, e.g. after a multipart upload, against
+    /// `expected_checksum` computed by the client while uploading, without downloading it
+    /// again. For an object uploaded in parts, that's the
+    /// [composite](ChecksumAlgorithm::composite) of the checksums of its parts, and the
+    /// checksums of the parts S3 lists with GetObjectAttributes have to add up to it as well.
+    ///
+    /// `false` on a mismatch, or if the object has no checksum computed with `algorithm`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::checksum::ChecksumAlgorithm;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let algorithm = ChecksumAlgorithm::Crc32;
+    /// // Computed on each part as it was uploaded
+    /// let part_checksums = [algorithm.compute(b"first part"), algorithm.compute(b"last part")];
+    /// let expected = algorithm.composite(&part_checksums).unwrap();
+    /// assert!(bucket.verify_upload("/test.file", &expected, algorithm).await?);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_upload<S: AsRef<str>>(
+        &self,
+        path: S,
+        expected_checksum: &str,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<bool, S3Error> {
+        let path = path.as_ref();
+        let attributes = self
+            .get_object_attributes_page(
+                path,
+                &[ObjectAttribute::Checksum, ObjectAttribute::ObjectParts],
+                None,
+            )
+            .await?;
+        // The number of parts may or may not follow the checksum itself
+        let expected_checksum = without_parts_count(expected_checksum);
+        match attributes
+            .checksum
+            .as_ref()
+            .and_then(|checksum| checksum.get(algorithm))
+        {
+            Some(checksum) if without_parts_count(checksum) == expected_checksum => {}
+            _ => return Ok(false),
+        }
+
+        let parts = self
+            .object_parts_from(path, attributes.object_parts)
+            .await?;
+        if parts.is_empty() {
+            return Ok(true);
+        }
+        let part_checksums = match parts
+            .iter()
+            .map(|part| part.checksum(algorithm))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(part_checksums) => part_checksums,
+            None => return Ok(false),
+        };
+        Ok(algorithm
+            .composite(&part_checksums)
+            .is_some_and(|composite| without_parts_count(&composite) == expected_checksum))
+    }
+
+    /// The parts listed on `page`, along with those on the pages following it.
+    async fn object_parts_from(
+        &self,
+        path: &str,
+        mut page: Option<ObjectParts>,
+    ) -> Result<Vec<ObjectPart>, S3Error> {
+        let mut parts = Vec::new();
+        while let Some(object_parts) = page {
+            parts.extend(object_parts.parts);
+            page = match object_parts.next_part_number_marker {
+                Some(marker) if object_parts.is_truncated => {
+                    self.get_object_attributes_page(
+                        path,
+                        &[ObjectAttribute::ObjectParts],
+                        Some(marker),
+                    )
+                    .await?
+                    .object_parts
+                }
+                _ => None,
+            };
+        }
+        Ok(parts)
+    }
+
+    /// HEAD `path`, failing unless the object exists.
+    async fn head_existing_object(&self, path: &str) -> Result<HeadObjectResult, S3Error> {
+        let (head_object_result, status) = self.head_object(path).await?;
+        existing_object(path, head_object_result, status)
+    }
+}
+
+/// `checksum` without the `-<parts count>` suffix of the checksum of an object uploaded in parts.
+fn without_parts_count(checksum: &str) -> &str {
+    checksum
+        .rsplit_once('-')
+        .map_or(checksum, |(checksum, _)| checksum)
+}
+
+/// The result of a HEAD of `path`, unless its `status` tells the object is missing, or is any
+/// other failure.
+pub(super) fn existing_object(
+    path: &str,
+    head_object_result: HeadObjectResult,
+    status: u16,
+) -> Result<HeadObjectResult, S3Error> {
+    match status {
+        404 => Err(S3Error::NoSuchKey {
+            key: path.to_string(),
+            delete_marker: head_object_result.delete_marker.unwrap_or(false),
+            request_id: head_object_result.request_id,
+            extended_request_id: head_object_result.extended_request_id,
+        }),
+        status if status >= 300 => {
+            // A HEAD has no body, the request ids are all there is to go by
+            let mut headers = http::HeaderMap::new();
+            let ids = [
+                ("x-amz-request-id", head_object_result.request_id),
+                ("x-amz-id-2", head_object_result.extended_request_id),
+            ];
+            for (name, id) in ids {
+                if let Some(value) = id.and_then(|id| http::HeaderValue::from_str(&id).ok()) {
+                    headers.insert(name, value);
+                }
+            }
+            Err(S3Error::HttpFailWithBody(
+                status,
+                String::new(),
+                Box::new(headers),
+            ))
+        }
+        _ => Ok(head_object_result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::head::existing_object;
+    use crate::bucket::HeadObjectModified;
+    use crate::bucket_ops::PutObjectOptions;
+    use crate::checksum::ChecksumAlgorithm;
+    use crate::command::Command;
+    use crate::error::S3Error;
+    use crate::request::{Request, RequestImpl};
+    use crate::serde_types::HeadObjectResult;
+    use crate::test_utils::{http_response, serve_once, serve_sequence};
+    use crate::utils::encode_metadata_value;
+    use time::macros::datetime;
+
+    #[tokio::test]
+    async fn test_stat() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[
+                    ("ETag", "\"etag\""),
+                    ("Last-Modified", "Fri, 24 May 2013 00:00:00 GMT"),
+                ],
+                "",
+            ),
+            http_response("404 Not Found", &[], ""),
+            http_response(
+                "403 Forbidden",
+                &[
+                    ("x-amz-request-id", "request-id"),
+                    ("x-amz-id-2", "extended-request-id"),
+                ],
+                "",
+            ),
+        ]);
+
+        let head_object_result = bucket.stat("/test.file").await.unwrap().unwrap();
+        assert_eq!(head_object_result.e_tag.as_deref(), Some("\"etag\""));
+        assert_eq!(
+            head_object_result.last_modified.as_deref(),
+            Some("Fri, 24 May 2013 00:00:00 GMT")
+        );
+        assert!(bucket.stat("/missing.file").await.unwrap().is_none());
+        let error = bucket.stat("/forbidden.file").await.unwrap_err();
+        assert_eq!(error.status_code(), Some(403));
+        assert_eq!(error.request_id(), Some("request-id"));
+        assert_eq!(error.extended_request_id(), Some("extended-request-id"));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .head
+            .starts_with("head /rust-s3-test/test.file http/1.1"));
+        assert!(requests[1]
+            .head
+            .starts_with("head /rust-s3-test/missing.file http/1.1"));
+    }
+
+    #[test]
+    fn test_existing_object_failure_keeps_request_ids() {
+        let head_object_result = HeadObjectResult {
+            request_id: Some("request-id".to_string()),
+            extended_request_id: Some("extended-request-id".to_string()),
+            ..Default::default()
+        };
+        let error = existing_object("/test.file", head_object_result, 412).unwrap_err();
+        assert_eq!(error.status_code(), Some(412));
+        assert_eq!(error.request_id(), Some("request-id"));
+        assert_eq!(error.extended_request_id(), Some("extended-request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_head_object_with_query() {
+        let version_id = "3HL4kqtJlcpXroDTDmJ+rmSpXd3dIbrHY";
+        let (bucket, server) = serve_sequence(vec![
+            http_response("200 OK", &[("x-amz-version-id", version_id)], ""),
+            http_response("200 OK", &[("x-amz-version-id", version_id)], ""),
+            http_response("200 OK", &[], ""),
+        ]);
+        let bucket = bucket.with_clock(std::sync::Arc::new(|| datetime!(2013-05-24 0:00 UTC)));
+        let mut queries = std::collections::HashMap::new();
+        queries.insert("versionId".to_string(), version_id.to_string());
+
+        let (head_object_result, status) = bucket
+            .head_object_with_query("/versioned.file", &queries)
+            .await
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(head_object_result.version_id.as_deref(), Some(version_id));
+        bucket
+            .head_object_version("/versioned.file", version_id)
+            .await
+            .unwrap();
+        bucket.head_object("/versioned.file").await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.starts_with(
+            "head /rust-s3-test/versioned.file?versionid=3hl4kqtjlcpxrodtdmj%2brmspxd3dibrhy http/1.1"
+        ));
+        // The query is signed: same signature as for the version, not as without it
+        let authorization = |head: &str| {
+            head.lines()
+                .find(|line| line.starts_with("authorization:"))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            authorization(&requests[0].head),
+            authorization(&requests[1].head)
+        );
+        assert_ne!(
+            authorization(&requests[0].head),
+            authorization(&requests[2].head)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_head_object_version() {
+        let version_id = "3HL4kqtJlcpXroDTDmJ+rmSpXd3dIbrHY";
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[("x-amz-version-id", version_id), ("ETag", "\"etag\"")],
+            "",
+        ));
+
+        let (head_object_result, status) = bucket
+            .head_object_version("/versioned.file", version_id)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with(
+            "head /rust-s3-test/versioned.file?versionid=3hl4kqtjlcpxrodtdmj%2brmspxd3dibrhy http/1.1"
+        ));
+        assert_eq!(status, 200);
+        assert_eq!(head_object_result.version_id.as_deref(), Some(version_id));
+
+        // The version is part of the signature
+        let command = Command::HeadObjectVersion { version_id };
+        let request = RequestImpl::new(&bucket, "/versioned.file", command).unwrap();
+        let headers = request.headers().unwrap();
+        let canonical_request = request.canonical_request(&headers).unwrap();
+        assert_eq!(
+            canonical_request.lines().nth(2),
+            Some("versionId=3HL4kqtJlcpXroDTDmJ%2BrmSpXd3dIbrHY")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_object_size_from_head() {
+        // A HEAD response has no body, whatever its Content-Length
+        let (bucket, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\nETag: \"etag\"\r\n\r\n".to_vec(),
+        );
+
+        let size = bucket.object_size("/test.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("head /rust-s3-test/test.file"));
+        assert_eq!(size, 1234);
+    }
+
+    #[tokio::test]
+    async fn test_object_size_from_attributes() {
+        let (bucket, server) = serve_sequence(vec![
+            // Neither a Content-Length nor chunked, nothing to take the size from
+            b"HTTP/1.1 200 OK\r\nETag: \"etag\"\r\nConnection: close\r\n\r\n".to_vec(),
+            http_response(
+                "200 OK",
+                &[],
+                "<GetObjectAttributesResponse><ObjectSize>5678</ObjectSize></GetObjectAttributesResponse>",
+            ),
+        ]);
+
+        let size = bucket.object_size("/test.file").await.unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.starts_with("head /rust-s3-test/test.file"));
+        assert!(requests[1]
+            .head
+            .starts_with("get /rust-s3-test/test.file?attributes http/1.1"));
+        assert!(requests[1]
+            .head
+            .contains("x-amz-object-attributes: objectsize\r\n"));
+        assert_eq!(size, 5678);
+    }
+
+    #[tokio::test]
+    async fn test_head_object_with_parts() {
+        let (bucket, server) = serve_sequence(vec![
+            b"HTTP/1.1 200 OK\r\nContent-Length: 12582912\r\nx-amz-mp-parts-count: 3\r\n\r\n"
+                .to_vec(),
+            http_response(
+                "200 OK",
+                &[],
+                "<GetObjectAttributesResponse><ObjectParts><PartsCount>3</PartsCount><IsTruncated>true</IsTruncated><NextPartNumberMarker>2</NextPartNumberMarker>\
+                 <Part><PartNumber>1</PartNumber><Size>5242880</Size><ChecksumCRC32>AAAAAA==</ChecksumCRC32></Part>\
+                 <Part><PartNumber>2</PartNumber><Size>5242880</Size></Part>\
+                 </ObjectParts></GetObjectAttributesResponse>",
+            ),
+            http_response(
+                "200 OK",
+                &[],
+                "<GetObjectAttributesResponse><ObjectParts><PartsCount>3</PartsCount><IsTruncated>false</IsTruncated>\
+                 <Part><PartNumber>3</PartNumber><Size>2097152</Size></Part>\
+                 </ObjectParts></GetObjectAttributesResponse>",
+            ),
+        ]);
+
+        let (head_object_result, parts) =
+            bucket.head_object_with_parts("/parts.file").await.unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!(head_object_result.parts_count, Some(3));
+        assert_eq!(
+            parts
+                .iter()
+                .map(|part| (part.part_number, part.size))
+                .collect::<Vec<_>>(),
+            [(1, 5242880), (2, 5242880), (3, 2097152)]
+        );
+        assert_eq!(parts[0].checksum_crc32.as_deref(), Some("AAAAAA=="));
+        assert!(requests[0]
+            .head
+            .starts_with("head /rust-s3-test/parts.file"));
+        assert!(requests[1]
+            .head
+            .starts_with("get /rust-s3-test/parts.file?attributes http/1.1"));
+        assert!(requests[1]
+            .head
+            .contains("x-amz-object-attributes: objectparts\r\n"));
+        assert!(!requests[1].head.contains("x-amz-part-number-marker"));
+        assert!(requests[2].head.contains("x-amz-part-number-marker: 2\r\n"));
+
+        // Uploaded with a single PutObject, nothing to follow up on
+        let (bucket, server) =
+            serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\n\r\n".to_vec());
+        let (head_object_result, parts) =
+            bucket.head_object_with_parts("/single.file").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(head_object_result.parts_count, None);
+        assert!(parts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verify_upload() {
+        let algorithm = ChecksumAlgorithm::Crc32;
+        let part_checksums = [
+            algorithm.compute(b"first part"),
+            algorithm.compute(b"last part"),
+        ];
+        let expected = algorithm.composite(&part_checksums).unwrap();
+        let attributes = |checksum: &str, last_part_checksum: &str| {
+            http_response(
+                "200 OK",
+                &[],
+                format!(
+                    "<GetObjectAttributesResponse><Checksum><ChecksumCRC32>{}</ChecksumCRC32></Checksum>\
+                     <ObjectParts><PartsCount>2</PartsCount><IsTruncated>false</IsTruncated>\
+                     <Part><PartNumber>1</PartNumber><Size>10</Size><ChecksumCRC32>{}</ChecksumCRC32></Part>\
+                     <Part><PartNumber>2</PartNumber><Size>9</Size><ChecksumCRC32>{}</ChecksumCRC32></Part>\
+                     </ObjectParts></GetObjectAttributesResponse>",
+                    checksum, part_checksums[0], last_part_checksum
+                ),
+            )
+        };
+
+        let (bucket, server) = serve_once(attributes(&expected, &part_checksums[1]));
+        assert!(bucket
+            .verify_upload("/parts.file", &expected, algorithm)
+            .await
+            .unwrap());
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .starts_with("get /rust-s3-test/parts.file?attributes http/1.1"));
+        assert!(request
+            .head
+            .contains("x-amz-object-attributes: checksum,objectparts\r\n"));
+
+        // The object's checksum doesn't match
+        let other = algorithm
+            .composite(&[algorithm.compute(b"other"), part_checksums[1].clone()])
+            .unwrap();
+        let (bucket, server) = serve_once(attributes(&other, &part_checksums[1]));
+        assert!(!bucket
+            .verify_upload("/parts.file", &expected, algorithm)
+            .await
+            .unwrap());
+        server.join().unwrap();
+
+        // A part's checksum doesn't add up to the object's
+        let (bucket, server) = serve_once(attributes(&expected, &algorithm.compute(b"other")));
+        assert!(!bucket
+            .verify_upload("/parts.file", &expected, algorithm)
+            .await
+            .unwrap());
+        server.join().unwrap();
+
+        // Uploaded without a checksum
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            "<GetObjectAttributesResponse><ETag>etag</ETag></GetObjectAttributesResponse>",
+        ));
+        assert!(!bucket
+            .verify_upload("/parts.file", &expected, algorithm)
+            .await
+            .unwrap());
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_non_ascii_metadata_round_trip() {
+        let title = "Café ☕ déjà vu";
+        let mut options = PutObjectOptions::default();
+        options
+            .metadata
+            .insert("title".to_string(), title.to_string());
+        options
+            .metadata
+            .insert("plain".to_string(), "just ascii".to_string());
+
+        let (bucket, server) = serve_once(http_response("200 OK", &[("ETag", "\"etag\"")], ""));
+        bucket
+            .put_object_with_options("/meta.file", b"", "text/plain", &options)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        // The test server lowercases what it received, base64 isn't case insensitive though
+        let encoded = encode_metadata_value(title);
+        assert!(encoded.starts_with("=?UTF-8?B?"));
+        assert!(request
+            .head
+            .contains(&format!("x-amz-meta-title: {}\r\n", encoded.to_lowercase())));
+        assert!(request.head.contains("x-amz-meta-plain: just ascii\r\n"));
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nx-amz-meta-title: {}\r\nx-amz-meta-plain: just ascii\r\n\r\n",
+            encoded
+        );
+        let (bucket, server) = serve_once(head.into_bytes());
+        let (head_object_result, _) = bucket.head_object("/meta.file").await.unwrap();
+        server.join().unwrap();
+        let metadata = head_object_result.metadata.unwrap();
+        assert_eq!(metadata["title"], title);
+        assert_eq!(metadata["plain"], "just ascii");
+    }
+
+    #[tokio::test]
+    async fn test_head_object_if_modified_since() {
+        let since = datetime!(2024-01-02 03:04:05 UTC);
+        let (bucket, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\nETag: \"changed\"\r\n\r\n".to_vec(),
+        );
+        let modified = bucket
+            .head_object_if_modified_since("/cached.file", since)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("head /rust-s3-test/cached.file"));
+        assert!(request
+            .head
+            .contains("if-modified-since: tue, 02 jan 2024 03:04:05 +0000\r\n"));
+        match modified {
+            HeadObjectModified::Modified(head_object_result) => {
+                assert_eq!(head_object_result.e_tag.as_deref(), Some("\"changed\""));
+                assert_eq!(head_object_result.content_length, Some(1234));
+            }
+            HeadObjectModified::NotModified => panic!("expected the object to be modified"),
+        }
+
+        let (bucket, server) =
+            serve_once(b"HTTP/1.1 304 Not Modified\r\nETag: \"same\"\r\n\r\n".to_vec());
+        let modified = bucket
+            .head_object_if_modified_since("/cached.file", since)
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert!(matches!(modified, HeadObjectModified::NotModified));
+    }
+
+    #[tokio::test]
+    async fn test_head_object_if_modified_since_missing_key() {
+        let (bucket, server) = serve_once(http_response("404 Not Found", &[], ""));
+        let error = bucket
+            .head_object_if_modified_since("/missing.file", datetime!(2024-01-02 0:00 UTC))
+            .await
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, S3Error::NoSuchKey { ref key, .. } if key == "/missing.file"));
+    }
+
+    #[tokio::test]
+    async fn test_head_object_missing_key_is_a_status() {
+        let (bucket, server) = serve_once(http_response("404 Not Found", &[], ""));
+
+        let (head_object_result, status) = bucket.head_object("/missing.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("head /rust-s3-test/missing.file"));
+        assert_eq!(status, 404);
+        assert_eq!(head_object_result.e_tag, None);
+    }
 }
@@ -1,16 +1,45 @@
-use crate::bucket::Bucket;
-use crate::command::Command;
+use crate::bucket::{error_from_response_data, Bucket};
+use crate::command::{Command, ConfigurationKind};
 use crate::error::S3Error;
 use crate::request::Request;
 use crate::request::RequestImpl;
-use crate::serde_types::{ListBucketResult, ListMultipartUploadsResult};
+use crate::serde_types::{
+    ListAnalyticsConfigurationsResult, ListBucketResult, ListInventoryConfigurationsResult,
+    ListMetricsConfigurationsResult, ListMultipartUploadsResult,
+};
 use awscreds::Credentials;
 use awsregion::Region;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use tracing::{field, span, Instrument, Level, Span};
+
+/// Name of the pseudo region of account level operations, served from `s3.amazonaws.com`.
+const AWS_GLOBAL: &str = "aws-global";
+
+/// The region to send an account level request to: `aws-global` is `us-east-1`, which
+/// `s3.amazonaws.com` signs requests for, unless it comes with an endpoint of its own.
+fn account_region(region: Region) -> Region {
+    match region {
+        Region::Custom { region, endpoint } if region == AWS_GLOBAL => {
+            if endpoint == AWS_GLOBAL {
+                Region::UsEast1
+            } else {
+                Region::Custom {
+                    region: Region::UsEast1.to_string(),
+                    endpoint,
+                }
+            }
+        }
+        region => region,
+    }
+}
 
 impl Bucket {
     /// Get a list of all existing buckets in the region
     /// that are accessible by the given credentials.
+    ///
+    /// Buckets are listed across all regions from the `aws-global` region, i.e.
+    /// `"aws-global".parse()?`, which is sent to `s3.amazonaws.com`.
     /// ```no_run
     /// use s3::{Bucket, BucketConfiguration};
     /// use s3::creds::Credentials;
@@ -36,7 +65,7 @@ impl Bucket {
         region: Region,
         credentials: Credentials,
     ) -> Result<crate::bucket::ListBucketsResponse, S3Error> {
-        let dummy_bucket = Bucket::new("", region, credentials)?.with_path_style();
+        let dummy_bucket = Bucket::new("", account_region(region), credentials)?.with_path_style();
         let request = RequestImpl::new(&dummy_bucket, "", Command::ListBuckets)?;
         let response = request.response_data(false).await?;
 
@@ -98,17 +127,18 @@ impl Bucket {
         } else {
             // In the v1 ListObjects request, there is only one "marker"
             // field that serves as both the initial starting position,
-            // and as the continuation token.
+            // and as the continuation token. `start_after` only positions
+            // the first page, the continuation token takes over after that.
             Command::ListObjects {
                 prefix,
                 delimiter,
-                marker: std::cmp::max(continuation_token, start_after),
+                marker: continuation_token.or(start_after),
                 max_keys,
             }
         };
         let request = RequestImpl::new(self, "/", command)?;
         let response_data = request.response_data(false).await?;
-        let list_bucket_result = quick_xml::de::from_reader(response_data.as_slice())?;
+        let list_bucket_result = response_data.deserialize_xml()?;
 
         Ok((list_bucket_result, response_data.status_code()))
     }
@@ -140,23 +170,64 @@ impl Bucket {
         prefix: String,
         delimiter: Option<String>,
     ) -> Result<Vec<ListBucketResult>, S3Error> {
-        let the_bucket = self.to_owned();
+        let (results, _) = self.list_from(prefix, delimiter, None).await?;
+        Ok(results)
+    }
+
+    /// List the contents of an S3 bucket like [`Bucket::list`], but stop early once the limits
+    /// in `options` are reached, e.g. to get the first 100 keys of a huge bucket.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::{Bucket, ListOptions};
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let options = ListOptions {
+    ///     max_keys: Some(100),
+    ///     ..Default::default()
+    /// };
+    /// let results = bucket.list_with("/".to_string(), options).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_with(
+        &self,
+        prefix: String,
+        options: ListOptions,
+    ) -> Result<Vec<ListBucketResult>, S3Error> {
         let mut results = Vec::new();
         let mut continuation_token = None;
+        let mut remaining_keys = options.max_keys;
 
-        loop {
-            let (list_bucket_result, _) = the_bucket
+        while !matches!(options.max_pages, Some(max_pages) if results.len() >= max_pages) {
+            let (list_bucket_result, _) = self
                 .list_page(
                     prefix.clone(),
-                    delimiter.clone(),
+                    options.delimiter.clone(),
                     continuation_token,
                     None,
-                    None,
+                    remaining_keys,
                 )
                 .await?;
             continuation_token = list_bucket_result.next_continuation_token.clone();
+            if let Some(remaining_keys) = remaining_keys.as_mut() {
+                let received =
+                    list_bucket_result.contents.len() + list_bucket_result.prefixes().count();
+                *remaining_keys = remaining_keys.saturating_sub(received);
+            }
             results.push(list_bucket_result);
-            if continuation_token.is_none() {
+            if continuation_token.is_none() || remaining_keys == Some(0) {
                 break;
             }
         }
@@ -164,22 +235,128 @@ impl Bucket {
         Ok(results)
     }
 
+    /// List the contents of an S3 bucket, starting at `continuation_token` instead of the
+    /// beginning, e.g. to resume a listing interrupted by a restart.
+    ///
+    /// Along with the pages, returns the continuation token the last page was requested with
+    /// (`None` if that was the first page). Persist it to pick up the listing again from that
+    /// page later on.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let checkpoint = std::fs::read_to_string("checkpoint").ok();
+    /// let (results, last_token) = bucket.list_from("/".to_string(), None, checkpoint).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_from(
+        &self,
+        prefix: String,
+        delimiter: Option<String>,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<ListBucketResult>, Option<String>), S3Error> {
+        let span = span!(
+            Level::DEBUG,
+            "list",
+            prefix = prefix.as_str(),
+            page_count = field::Empty,
+        );
+        async move {
+            let the_bucket = self.to_owned();
+            let mut results = Vec::new();
+            let mut continuation_token = continuation_token;
+
+            let last_token = loop {
+                let (list_bucket_result, _) = the_bucket
+                    .list_page(
+                        prefix.clone(),
+                        delimiter.clone(),
+                        continuation_token.clone(),
+                        None,
+                        None,
+                    )
+                    .await?;
+                let next_token = list_bucket_result.next_continuation_token.clone();
+                results.push(list_bucket_result);
+                if next_token.is_none() {
+                    break continuation_token;
+                }
+                continuation_token = next_token;
+            };
+
+            Span::current().record("page_count", results.len());
+            Ok((results, last_token))
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// List the immediate "directories" under `prefix`, i.e. the common prefixes of the keys
+    /// up to the next `/`, across all pages and without duplicates. A `prefix` without a
+    /// trailing `/` is treated as the directory of that name.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // e.g. ["photos/2022/", "photos/2023/"]
+    /// let directories = bucket.list_directories("photos").await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_directories(&self, prefix: &str) -> Result<Vec<String>, S3Error> {
+        let mut prefix = prefix.to_string();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        let results = self.list(prefix, Some("/".to_string())).await?;
+        Ok(ListBucketResult::flatten(&results).common_prefixes)
+    }
+
     pub async fn list_multiparts_uploads_page(
         &self,
         prefix: Option<&str>,
         delimiter: Option<&str>,
         key_marker: Option<String>,
+        upload_id_marker: Option<String>,
         max_uploads: Option<usize>,
     ) -> Result<(ListMultipartUploadsResult, u16), S3Error> {
         let command = Command::ListMultipartUploads {
             prefix,
             delimiter,
             key_marker,
+            upload_id_marker,
             max_uploads,
         };
         let request = RequestImpl::new(self, "/", command)?;
         let response_data = request.response_data(false).await?;
-        let list_bucket_result = quick_xml::de::from_reader(response_data.as_slice())?;
+        let list_bucket_result = response_data.deserialize_xml()?;
 
         Ok((list_bucket_result, response_data.status_code()))
     }
@@ -215,14 +392,22 @@ impl Bucket {
         let the_bucket = self.to_owned();
         let mut results = Vec::new();
         let mut next_marker: Option<String> = None;
+        let mut next_upload_id_marker: Option<String> = None;
 
         loop {
             let (list_multiparts_uploads_result, _) = the_bucket
-                .list_multiparts_uploads_page(prefix, delimiter, next_marker, None)
+                .list_multiparts_uploads_page(
+                    prefix,
+                    delimiter,
+                    next_marker,
+                    next_upload_id_marker,
+                    None,
+                )
                 .await?;
 
             let is_truncated = list_multiparts_uploads_result.is_truncated;
             next_marker = list_multiparts_uploads_result.next_marker.clone();
+            next_upload_id_marker = list_multiparts_uploads_result.next_upload_id_marker.clone();
             results.push(list_multiparts_uploads_result);
 
             if !is_truncated {
@@ -232,6 +417,136 @@ impl Bucket {
 
         Ok(results)
     }
+
+    /// List a page of up to 100 inventory configurations of the bucket, continuing after the page that returned
+    /// `continuation_token` if given.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut page = bucket.list_bucket_inventory_configurations(None).await?;
+    /// while let Some(token) = page.next_continuation_token.clone() {
+    ///     page = bucket.list_bucket_inventory_configurations(Some(&token)).await?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_bucket_inventory_configurations(
+        &self,
+        continuation_token: Option<&str>,
+    ) -> Result<ListInventoryConfigurationsResult, S3Error> {
+        self.list_bucket_configurations(ConfigurationKind::Inventory, continuation_token)
+            .await
+    }
+
+    /// List a page of up to 100 storage class analysis configurations of the bucket, continuing after the page that returned
+    /// `continuation_token` if given.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut page = bucket.list_bucket_analytics_configurations(None).await?;
+    /// while let Some(token) = page.next_continuation_token.clone() {
+    ///     page = bucket.list_bucket_analytics_configurations(Some(&token)).await?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_bucket_analytics_configurations(
+        &self,
+        continuation_token: Option<&str>,
+    ) -> Result<ListAnalyticsConfigurationsResult, S3Error> {
+        self.list_bucket_configurations(ConfigurationKind::Analytics, continuation_token)
+            .await
+    }
+
+    /// List a page of up to 100 request metrics configurations of the bucket, continuing after the page that returned
+    /// `continuation_token` if given.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let mut page = bucket.list_bucket_metrics_configurations(None).await?;
+    /// while let Some(token) = page.next_continuation_token.clone() {
+    ///     page = bucket.list_bucket_metrics_configurations(Some(&token)).await?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_bucket_metrics_configurations(
+        &self,
+        continuation_token: Option<&str>,
+    ) -> Result<ListMetricsConfigurationsResult, S3Error> {
+        self.list_bucket_configurations(ConfigurationKind::Metrics, continuation_token)
+            .await
+    }
+
+    async fn list_bucket_configurations<T: DeserializeOwned>(
+        &self,
+        kind: ConfigurationKind,
+        continuation_token: Option<&str>,
+    ) -> Result<T, S3Error> {
+        let command = Command::ListBucketConfigurations {
+            kind,
+            continuation_token,
+        };
+        let request = RequestImpl::new(self, "/", command)?;
+        let response_data = request.response_data(false).await?;
+        if response_data.status_code() >= 300 {
+            return Err(error_from_response_data(response_data)?);
+        }
+        response_data.deserialize_xml()
+    }
+}
+
+/// Limits of a listing with [`Bucket::list_with`].
+#[derive(Clone, Debug, Default)]
+pub struct ListOptions {
+    /// Group keys sharing a prefix up to the delimiter into common prefixes
+    pub delimiter: Option<String>,
+    /// Stop after this many keys and common prefixes, across all pages
+    pub max_keys: Option<usize>,
+    /// Stop after this many pages
+    pub max_pages: Option<usize>,
 }
 
 #[derive(Clone, Default, Deserialize, Debug)]
@@ -271,6 +586,239 @@ pub struct BucketContainer {
 
 #[cfg(test)]
 mod tests {
+    use super::{account_region, ListOptions};
+    use crate::bucket::Bucket;
+    use crate::test_utils::{fake_credentials, http_response, serve_once, serve_sequence};
+    use awsregion::Region;
+
+    fn truncated_page(key: &str, token: &str) -> Vec<u8> {
+        http_response(
+            "200 OK",
+            &[],
+            format!(
+                r#"<ListBucketResult>
+                    <Name>rust-s3-test</Name>
+                    <Prefix></Prefix>
+                    <IsTruncated>true</IsTruncated>
+                    <NextContinuationToken>{}</NextContinuationToken>
+                    <Contents>
+                        <Key>{}</Key>
+                        <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                        <Size>4</Size>
+                    </Contents>
+                </ListBucketResult>"#,
+                token, key
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_list_page_v1_start_after() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                r#"<ListBucketResult>
+                    <Name>rust-s3-test</Name>
+                    <Prefix>logs/</Prefix>
+                    <Marker>logs/2023-06</Marker>
+                    <IsTruncated>true</IsTruncated>
+                    <NextMarker>logs/2023-06-02.txt</NextMarker>
+                </ListBucketResult>"#,
+            ),
+            http_response(
+                "200 OK",
+                &[],
+                r#"<ListBucketResult>
+                    <Name>rust-s3-test</Name>
+                    <Prefix>logs/</Prefix>
+                    <IsTruncated>false</IsTruncated>
+                </ListBucketResult>"#,
+            ),
+        ]);
+        let bucket = bucket.with_listobjects_v1();
+        let start_after = Some("logs/2023-06".to_string());
+
+        let (first, _) = bucket
+            .list_page("logs/".to_string(), None, None, start_after.clone(), None)
+            .await
+            .unwrap();
+        bucket
+            .list_page(
+                "logs/".to_string(),
+                None,
+                first.next_continuation_token,
+                start_after,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.contains("&marker=logs%2f2023-06 http/1.1"));
+        assert!(requests[1]
+            .head
+            .contains("&marker=logs%2f2023-06-02.txt http/1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_list_from_continuation_token() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Name>rust-s3-test</Name>
+                <Prefix></Prefix>
+                <ContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</ContinuationToken>
+                <KeyCount>1</KeyCount>
+                <MaxKeys>1000</MaxKeys>
+                <IsTruncated>false</IsTruncated>
+                <Contents>
+                    <Key>last.file</Key>
+                    <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                    <ETag>"etag"</ETag>
+                    <Size>4</Size>
+                    <StorageClass>STANDARD</StorageClass>
+                </Contents>
+            </ListBucketResult>"#,
+        ));
+
+        let token = "1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=".to_string();
+        let (results, last_token) = bucket
+            .list_from(String::new(), None, Some(token.clone()))
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request
+            .head
+            .contains("continuation-token=1uegcxlprx1tr%2fxyexhnhbylgveds2j%2fwm36hy4vbowm%3d"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].contents[0].key, "last.file");
+        assert_eq!(last_token, Some(token));
+    }
+
+    #[tokio::test]
+    async fn test_list_with_limits() {
+        let (bucket, server) = serve_sequence(vec![
+            truncated_page("a.file", "token-1"),
+            truncated_page("b.file", "token-2"),
+        ]);
+        let options = ListOptions {
+            max_pages: Some(2),
+            ..Default::default()
+        };
+        let results = bucket.list_with(String::new(), options).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(server.join().unwrap().len(), 2);
+
+        let (bucket, server) = serve_sequence(vec![
+            truncated_page("a.file", "token-1"),
+            truncated_page("b.file", "token-2"),
+        ]);
+        let options = ListOptions {
+            max_keys: Some(2),
+            ..Default::default()
+        };
+        let results = bucket.list_with(String::new(), options).await.unwrap();
+        assert_eq!(results.len(), 2);
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.contains("max-keys=2"));
+        assert!(requests[1].head.contains("max-keys=1"));
+    }
+
+    #[tokio::test]
+    async fn test_list_directories() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "200 OK",
+                &[],
+                r#"<ListBucketResult>
+                    <Name>rust-s3-test</Name>
+                    <Prefix>photos/</Prefix>
+                    <Delimiter>/</Delimiter>
+                    <IsTruncated>true</IsTruncated>
+                    <NextContinuationToken>token</NextContinuationToken>
+                    <Contents>
+                        <Key>photos/index.html</Key>
+                        <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                        <Size>4</Size>
+                    </Contents>
+                    <CommonPrefixes><Prefix>photos/2021/</Prefix></CommonPrefixes>
+                    <CommonPrefixes><Prefix>photos/2022/</Prefix></CommonPrefixes>
+                </ListBucketResult>"#,
+            ),
+            http_response(
+                "200 OK",
+                &[],
+                r#"<ListBucketResult>
+                    <Name>rust-s3-test</Name>
+                    <Prefix>photos/</Prefix>
+                    <Delimiter>/</Delimiter>
+                    <IsTruncated>false</IsTruncated>
+                    <CommonPrefixes><Prefix>photos/2022/</Prefix></CommonPrefixes>
+                    <CommonPrefixes><Prefix>photos/2023/</Prefix></CommonPrefixes>
+                </ListBucketResult>"#,
+            ),
+        ]);
+
+        let directories = bucket.list_directories("photos").await.unwrap();
+        assert_eq!(
+            directories,
+            vec!["photos/2021/", "photos/2022/", "photos/2023/"]
+        );
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.contains("prefix=photos%2f"));
+        assert!(requests[0].head.contains("delimiter=%2f"));
+        assert!(requests[1].head.contains("continuation-token=token"));
+    }
+
+    #[test]
+    fn test_aws_global_region() {
+        let region = account_region("aws-global".parse().unwrap());
+        assert_eq!(region, Region::UsEast1);
+        assert_eq!(region.host(), "s3.amazonaws.com");
+
+        // Other regions are left alone
+        let region = account_region("eu-west-1".parse().unwrap());
+        assert_eq!(region, Region::EuWest1);
+    }
+
+    #[tokio::test]
+    async fn test_list_buckets_aws_global() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[],
+            r#"<ListAllMyBucketsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Owner><ID>owner-id</ID><DisplayName>owner</DisplayName></Owner>
+                <Buckets>
+                    <Bucket>
+                        <Name>rust-s3-test</Name>
+                        <CreationDate>2023-06-04T20:13:37.837Z</CreationDate>
+                    </Bucket>
+                </Buckets>
+            </ListAllMyBucketsResult>"#,
+        ));
+        // Stand in for s3.amazonaws.com
+        let region = Region::Custom {
+            region: "aws-global".to_string(),
+            endpoint: bucket.region().endpoint(),
+        };
+
+        let response = Bucket::list_buckets(region, fake_credentials())
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.starts_with("get / http/1.1"));
+        // Signed for the region s3.amazonaws.com is in
+        assert!(request.head.contains("/us-east-1/s3/aws4_request"));
+        assert_eq!(
+            response.bucket_names().collect::<Vec<String>>(),
+            vec!["rust-s3-test"]
+        );
+    }
+
     #[test]
     pub fn parse_list_buckets_response() {
         let response = r#"
@@ -342,4 +890,99 @@ mod tests {
         );
         assert_eq!(parsed.buckets.bucket.len(), 0);
     }
+
+    mod spans {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        #[derive(Debug, Clone)]
+        pub struct RecordedSpan {
+            pub id: u64,
+            pub name: &'static str,
+            pub parent: Option<u64>,
+        }
+
+        /// Minimal subscriber recording every span along with its parent.
+        #[derive(Default)]
+        pub struct SpanRecorder {
+            next_id: AtomicU64,
+            stack: Mutex<Vec<u64>>,
+            pub spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        }
+
+        impl Subscriber for SpanRecorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+                let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+                let parent = if let Some(parent) = attrs.parent() {
+                    Some(parent.into_u64())
+                } else if attrs.is_contextual() {
+                    self.stack.lock().unwrap().last().copied()
+                } else {
+                    None
+                };
+                self.spans.lock().unwrap().push(RecordedSpan {
+                    id,
+                    name: attrs.metadata().name(),
+                    parent,
+                });
+                Id::from_u64(id)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, _: &Event<'_>) {}
+
+            fn enter(&self, span: &Id) {
+                self.stack.lock().unwrap().push(span.into_u64());
+            }
+
+            fn exit(&self, span: &Id) {
+                let mut stack = self.stack.lock().unwrap();
+                if let Some(position) = stack.iter().rposition(|id| *id == span.into_u64()) {
+                    stack.remove(position);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn list_span_wraps_request_spans() {
+        use crate::creds::Credentials;
+        use crate::region::Region;
+        use crate::Bucket;
+
+        let recorder = spans::SpanRecorder::default();
+        let recorded = recorder.spans.clone();
+        let _guard = tracing::subscriber::set_default(recorder);
+
+        // Nothing listens here, we only care about the spans opened on the way.
+        let bucket = Bucket::new(
+            "rust-s3-test",
+            Region::Custom {
+                region: "eu-central-1".to_owned(),
+                endpoint: "http://127.0.0.1:1".to_owned(),
+            },
+            Credentials::new(Some("access_key"), Some("secret_key"), None, None, None).unwrap(),
+        )
+        .unwrap()
+        .with_path_style();
+        assert!(bucket.list("photos/".to_string(), None).await.is_err());
+
+        let recorded = recorded.lock().unwrap();
+        let list_span = recorded.iter().find(|span| span.name == "list").unwrap();
+        let request_span = recorded
+            .iter()
+            .find(|span| span.name == "rust-s3-async")
+            .unwrap();
+        assert_eq!(list_span.parent, None);
+        assert_eq!(request_span.parent, Some(list_span.id));
+    }
 }
@@ -1,18 +1,22 @@
 mod presign;
+use crate::clock::{Clock, ClockFn};
 use crate::error::S3Error;
+use crate::metrics::{Metrics, S3Metrics};
+use crate::resolver::Resolver;
+use crate::retry::RetryPolicy;
 use awscreds::Credentials;
 use awsregion::Region;
 use http::HeaderMap;
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicI64;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
+use time::OffsetDateTime;
 
 pub use std::io::Read;
 
-pub use presign::*;
-
 mod credentials;
-pub use credentials::*;
 
 mod tag;
 pub use tag::*;
@@ -24,38 +28,47 @@ mod list;
 pub use list::*;
 
 mod delete;
-pub use delete::*;
 
 mod copy;
-pub use copy::*;
 
 mod get;
-pub use get::*;
 
 mod put;
-pub use put::*;
 
 mod head;
-pub use head::*;
 
 mod utils;
-pub use utils::*;
 
 pub type Query = HashMap<String, String>;
 
 pub use crate::serde_types::{
-    BucketLocationResult, CompleteMultipartUploadData, CorsConfiguration, HeadObjectResult,
-    InitiateMultipartUploadResponse, ListBucketResult, ListMultipartUploadsResult, Part,
+    AccelerateConfiguration, AccelerateStatus, AccessControlList, AccessControlPolicy,
+    AnalyticsConfiguration, ApplyServerSideEncryptionByDefault, BucketLocationResult,
+    CompleteMultipartUploadData, ConfigurationFilter, ConfigurationFilterAnd, CorsConfiguration,
+    DeleteObjectError, DeleteObjectsRequest, DeleteObjectsResult, DeletedObject, FilterRule,
+    FilterTag, GetObjectAttributesOutput, Grant, Grantee, HeadObjectResult,
+    InitiateMultipartUploadResponse, InventoryConfiguration, InventoryFormat, InventoryFrequency,
+    InventoryIncludedObjectVersions, LambdaFunctionConfiguration,
+    ListAnalyticsConfigurationsResult, ListBucketEntries, ListBucketResult,
+    ListInventoryConfigurationsResult, ListMetricsConfigurationsResult, ListMultipartUploadsResult,
+    MetricsConfiguration, NotificationConfiguration, NotificationFilter, ObjectAttribute,
+    ObjectChecksum, ObjectIdentifier, ObjectOwnership, ObjectPart, ObjectParts, Owner,
+    OwnershipControls, OwnershipControlsRule, Part, QueueConfiguration,
+    ServerSideEncryptionConfiguration, ServerSideEncryptionRule, TopicConfiguration,
 };
-pub(crate) use crate::utils::error_from_response_data;
-pub use crate::utils::PutStreamResponse;
+pub(crate) use crate::utils::{acl_not_supported, error_from_response_data, precondition_failed};
+pub use crate::utils::{DeleteObjectOutput, HeadObjectModified, PutStreamResponse};
 
 pub use crate::request::Request;
 
 pub const CHUNK_SIZE: usize = 8_388_608; // 8 Mebibytes, min is 5 (5_242_880);
+const MIN_PART_SIZE: usize = 5_242_880;
 
 /// Instantiate an existing Bucket
 ///
+/// Objects are addressed by their path in the bucket, with or without a leading slash:
+/// `/test.file` and `test.file` are the same object, see [`object_key`](crate::utils::object_key).
+///
 /// # Example
 ///
 /// ```no_run
@@ -76,83 +89,240 @@ pub struct Bucket {
     pub extra_headers: HeaderMap,
     pub extra_query: Query,
     pub request_timeout: Option<Duration>,
+    pub retry_policy: RetryPolicy,
     path_style: bool,
     listobjects_v2: bool,
+    transfer_acceleration: bool,
+    dualstack: bool,
+    resolver: Option<Resolver>,
+    auto_decompress: bool,
+    unsigned_payload: bool,
+    multipart_threshold: usize,
+    max_response_size: Option<usize>,
+    host_header: Option<String>,
+    metrics: Option<Metrics>,
+    clock: Option<Clock>,
+    clock_skew_correction: bool,
+    /// Seconds the clock is behind the server, shared by the clones of the bucket
+    clock_offset: Arc<AtomicI64>,
 }
 
 const DEFAULT_REQUEST_TIMEOUT: Option<Duration> = Some(Duration::from_secs(60));
 
+/// Presigned urls are valid for at least a second and at most a week.
 fn validate_expiry(expiry_secs: u32) -> Result<(), S3Error> {
+    if expiry_secs == 0 {
+        return Err(S3Error::MinExpiry(expiry_secs));
+    }
     if 604800 < expiry_secs {
         return Err(S3Error::MaxExpiry(expiry_secs));
     }
     Ok(())
 }
 
-impl Bucket {
-    pub fn with_path_style(&self) -> Self {
-        Self {
-            name: self.name.clone(),
-            region: self.region.clone(),
-            credentials: self.credentials.clone(),
-            extra_headers: self.extra_headers.clone(),
-            extra_query: self.extra_query.clone(),
-            request_timeout: self.request_timeout,
-            path_style: true,
-            listobjects_v2: self.listobjects_v2,
+/// A url or policy signed as of `datetime` with temporary credentials stops working when they
+/// expire, so it can't be valid for longer than they are.
+pub(crate) fn validate_expiry_within_credentials(
+    bucket: &Bucket,
+    expiry_secs: u32,
+    datetime: OffsetDateTime,
+) -> Result<(), S3Error> {
+    let expiration = bucket
+        .credentials()
+        .read()
+        .map_err(|_| S3Error::RLCredentials)?
+        .expiration;
+    if let Some(expiration) = expiration {
+        let remaining_secs = (*expiration - datetime).whole_seconds();
+        if i64::from(expiry_secs) > remaining_secs {
+            return Err(S3Error::ExpiryExceedsCredentials {
+                expiry_secs,
+                remaining_secs,
+            });
         }
     }
+    Ok(())
+}
 
-    pub fn with_extra_headers(&self, extra_headers: HeaderMap) -> Self {
-        Self {
-            name: self.name.clone(),
-            region: self.region.clone(),
-            credentials: self.credentials.clone(),
-            extra_headers,
-            extra_query: self.extra_query.clone(),
-            request_timeout: self.request_timeout,
-            path_style: self.path_style,
-            listobjects_v2: self.listobjects_v2,
-        }
+impl Bucket {
+    /// Address the bucket in the path, `<endpoint>/<bucket>`, instead of the hostname.
+    ///
+    /// Like the other `with_*` builders, this takes the bucket by value and changes it in
+    /// place, so chaining them doesn't copy it over and over. Clone the bucket first to keep
+    /// the original around.
+    pub fn with_path_style(mut self) -> Self {
+        self.path_style = true;
+        self
     }
 
-    pub fn with_extra_query(&self, extra_query: HashMap<String, String>) -> Self {
-        Self {
-            name: self.name.clone(),
-            region: self.region.clone(),
-            credentials: self.credentials.clone(),
-            extra_headers: self.extra_headers.clone(),
-            extra_query,
-            request_timeout: self.request_timeout,
-            path_style: self.path_style,
-            listobjects_v2: self.listobjects_v2,
-        }
+    /// Use `credentials` for the bucket. The credentials of a clone taken before aren't
+    /// shared with it, so refreshing or replacing them on one bucket leaves the other alone.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let template = Bucket::new(bucket_name, region, credentials).unwrap();
+    ///
+    /// let other = Credentials::from_profile(Some("other-account")).unwrap();
+    /// let bucket = template.clone().with_credentials(other);
+    /// ```
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Arc::new(RwLock::new(credentials));
+        self
     }
 
-    pub fn with_request_timeout(&self, request_timeout: Duration) -> Self {
+    /// The bucket called `name` in the same region, with the same settings. The credentials
+    /// are shared, refreshing them on one of the buckets refreshes them on all.
+    pub fn clone_with_name(&self, name: &str) -> Self {
         Self {
-            name: self.name.clone(),
-            region: self.region.clone(),
-            credentials: self.credentials.clone(),
-            extra_headers: self.extra_headers.clone(),
-            extra_query: self.extra_query.clone(),
-            request_timeout: Some(request_timeout),
-            path_style: self.path_style,
-            listobjects_v2: self.listobjects_v2,
+            name: name.to_string(),
+            ..self.clone()
         }
     }
 
-    pub fn with_listobjects_v1(&self) -> Self {
-        Self {
-            name: self.name.clone(),
-            region: self.region.clone(),
-            credentials: self.credentials.clone(),
-            extra_headers: self.extra_headers.clone(),
-            extra_query: self.extra_query.clone(),
-            request_timeout: self.request_timeout,
-            path_style: self.path_style,
-            listobjects_v2: false,
-        }
+    pub fn with_extra_headers(mut self, extra_headers: HeaderMap) -> Self {
+        self.extra_headers = extra_headers;
+        self
+    }
+
+    pub fn with_extra_query(mut self, extra_query: HashMap<String, String>) -> Self {
+        self.extra_query = extra_query;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_listobjects_v1(mut self) -> Self {
+        self.listobjects_v2 = false;
+        self
+    }
+
+    /// Route requests through the S3 Transfer Acceleration endpoint,
+    /// `<bucket>.s3-accelerate.amazonaws.com`, instead of the regional one.
+    ///
+    /// Acceleration has to be enabled on the bucket first, see
+    /// [`Bucket::put_bucket_accelerate_configuration`]. It only works with subdomain style
+    /// requests and DNS compatible bucket names without dots; requests on other buckets fail
+    /// with [`S3Error::TransferAcceleration`].
+    pub fn with_transfer_acceleration(mut self, transfer_acceleration: bool) -> Self {
+        self.transfer_acceleration = transfer_acceleration;
+        self
+    }
+
+    /// Use the dual-stack (IPv4 and IPv6) AWS endpoints, `s3.dualstack.<region>.amazonaws.com`
+    /// or `s3-accelerate.dualstack.amazonaws.com` with transfer acceleration. Endpoints outside
+    /// of AWS are left as they are.
+    pub fn with_dualstack(mut self, dualstack: bool) -> Self {
+        self.dualstack = dualstack;
+        self
+    }
+
+    /// Resolve the endpoint host with `resolve` instead of the system DNS resolver, e.g. to
+    /// reach the endpoint at a fixed address. See [`Resolver`].
+    pub fn with_resolver<F>(mut self, resolve: F) -> Self
+    where
+        F: Fn(&str) -> Vec<SocketAddr> + Send + Sync + 'static,
+    {
+        self.resolver = Some(Resolver::new(resolve));
+        self
+    }
+
+    /// Transparently inflate `gzip` and `deflate` encoded objects fetched with
+    /// [`Bucket::get_object`] and [`Bucket::get_object_to_writer`], according to their
+    /// `Content-Encoding`. The header itself is left in the response for inspection.
+    /// Disabled by default, objects are returned as stored.
+    pub fn with_auto_decompress(mut self, auto_decompress: bool) -> Self {
+        self.auto_decompress = auto_decompress;
+        self
+    }
+
+    /// Send the content of uploaded objects and parts with `UNSIGNED-PAYLOAD` as its
+    /// `x-amz-content-sha256`, instead of hashing it, which is significant CPU time on large
+    /// objects. The payload is then left to TLS to protect, so this is only meant for HTTPS
+    /// endpoints. Uploads given a precomputed SHA256 still send it. Disabled by default.
+    pub fn with_unsigned_payload(mut self, unsigned_payload: bool) -> Self {
+        self.unsigned_payload = unsigned_payload;
+        self
+    }
+
+    /// Upload objects of at least `multipart_threshold` bytes with
+    /// [`Bucket::put_object_stream`] as multipart uploads, in parts of [`CHUNK_SIZE`], and
+    /// smaller ones with a single PutObject. Up to `multipart_threshold` bytes are buffered to
+    /// decide. Defaults to [`CHUNK_SIZE`], a threshold below the 5 MiB minimum part size is
+    /// rejected once an object reaches it.
+    pub fn with_multipart_threshold(mut self, multipart_threshold: usize) -> Self {
+        self.multipart_threshold = multipart_threshold;
+        self
+    }
+
+    /// Fail requests buffering a response body of more than `max_response_size` bytes, like
+    /// [`Bucket::get_object`], with [`S3Error::ResponseTooLarge`] instead of holding it all in
+    /// memory. Streaming methods, like [`Bucket::get_object_to_writer`] and
+    /// [`Bucket::get_object_stream`], are not limited. Unlimited if `None`, the default.
+    pub fn with_max_response_size(mut self, max_response_size: Option<usize>) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Send `host_header` as the `Host` header, and sign it, instead of the endpoint host. The
+    /// connection, and with it TLS SNI, still goes to the endpoint, or to the address of the
+    /// [resolver](Bucket::with_resolver). Meant for proxies and tunnels that route on `Host`.
+    pub fn with_host_header(mut self, host_header: impl Into<String>) -> Self {
+        self.host_header = Some(host_header.into());
+        self
+    }
+
+    /// Invoke the callbacks of `metrics` around every request sent for the bucket.
+    /// See [`S3Metrics`].
+    pub fn with_metrics(mut self, metrics: Arc<dyn S3Metrics>) -> Self {
+        self.metrics = Some(Metrics(metrics));
+        self
+    }
+
+    /// Sign requests and presigned URLs as of the time returned by `clock` instead of the system
+    /// time, e.g. for reproducible tests, or to make up for a system clock that is off.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use std::sync::Arc;
+    /// use time::macros::datetime;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    ///
+    /// let bucket = Bucket::new(bucket_name, region, credentials)
+    ///     .unwrap()
+    ///     .with_clock(Arc::new(|| datetime!(2013-05-24 0:00 UTC)));
+    /// ```
+    pub fn with_clock(mut self, clock: Arc<ClockFn>) -> Self {
+        self.clock = Some(Clock(clock));
+        self
+    }
+
+    /// When a request is rejected with `RequestTimeTooSkewed`, because the clock is too far off
+    /// the server's, sign it again as of the server's time, taken from the `Date` header of the
+    /// response, and keep making up for the difference in later requests. Disabled by default.
+    pub fn with_clock_skew_correction(mut self, clock_skew_correction: bool) -> Self {
+        self.clock_skew_correction = clock_skew_correction;
+        self
     }
 
     pub(crate) fn _tags_xml<S: AsRef<str>>(&self, tags: &[(S, S)]) -> String {
@@ -5,6 +5,9 @@ use http::HeaderName;
 use crate::bucket::*;
 use crate::command::Command;
 use crate::request::RequestImpl;
+use crate::retry::RetryPolicy;
+use std::sync::atomic::Ordering;
+use time::OffsetDateTime;
 
 impl Bucket {
     /// Get path_style field of the Bucket struct
@@ -29,14 +32,158 @@ impl Bucket {
 
     /// Configure bucket to apply this request timeout to all HTTP
     /// requests, or no (infinity) timeout if `None`.  Defaults to
-    /// 30 seconds.
+    /// 60 seconds.
     ///
-    /// Only the [`hyper`] backend obeys this option;
-    /// async code may instead await with a timeout.
+    /// The timeout bounds every attempt from sending the request until the response body
+    /// has been read, requests running out of time fail with [`S3Error::RequestTimeout`].
+    /// That includes downloads to a file or writer, so allow for the size of the object.
+    /// Streamed responses, such as [`Bucket::get_object_stream`], are only bounded until
+    /// their headers arrive.
     pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
         self.request_timeout = timeout;
     }
 
+    /// Configure how requests failing with a transient error are retried.
+    /// Defaults to no retries.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Configure bucket to go through the S3 Transfer Acceleration endpoint
+    pub fn set_transfer_acceleration(&mut self, transfer_acceleration: bool) {
+        self.transfer_acceleration = transfer_acceleration;
+    }
+
+    /// Get transfer_acceleration field of the Bucket struct
+    pub fn is_transfer_accelerated(&self) -> bool {
+        self.transfer_acceleration
+    }
+
+    /// Configure bucket to use the dual-stack (IPv4 and IPv6) AWS endpoints
+    pub fn set_dualstack(&mut self, dualstack: bool) {
+        self.dualstack = dualstack;
+    }
+
+    /// Get dualstack field of the Bucket struct
+    pub fn is_dualstack(&self) -> bool {
+        self.dualstack
+    }
+
+    /// Configure how the endpoint host is resolved, or use the system DNS resolver if `None`
+    pub fn set_resolver(&mut self, resolver: Option<Resolver>) {
+        self.resolver = resolver;
+    }
+
+    /// Get the resolver overriding the system DNS resolver, if any
+    pub fn resolver(&self) -> Option<Resolver> {
+        self.resolver.clone()
+    }
+
+    /// Configure bucket to inflate `gzip` and `deflate` encoded objects on download
+    pub fn set_auto_decompress(&mut self, auto_decompress: bool) {
+        self.auto_decompress = auto_decompress;
+    }
+
+    /// Get auto_decompress field of the Bucket struct
+    pub fn is_auto_decompress(&self) -> bool {
+        self.auto_decompress
+    }
+
+    /// Configure bucket to send uploaded content as `UNSIGNED-PAYLOAD` instead of hashing it
+    pub fn set_unsigned_payload(&mut self, unsigned_payload: bool) {
+        self.unsigned_payload = unsigned_payload;
+    }
+
+    /// Get unsigned_payload field of the Bucket struct
+    pub fn is_unsigned_payload(&self) -> bool {
+        self.unsigned_payload
+    }
+
+    /// Configure the object size from which streamed uploads are multipart uploads
+    pub fn set_multipart_threshold(&mut self, multipart_threshold: usize) {
+        self.multipart_threshold = multipart_threshold;
+    }
+
+    /// Get the object size from which streamed uploads are multipart uploads
+    pub fn multipart_threshold(&self) -> usize {
+        self.multipart_threshold
+    }
+
+    /// Configure the size above which buffered response bodies are rejected, or none if `None`
+    pub fn set_max_response_size(&mut self, max_response_size: Option<usize>) {
+        self.max_response_size = max_response_size;
+    }
+
+    /// Get the size above which buffered response bodies are rejected, if any
+    pub fn max_response_size(&self) -> Option<usize> {
+        self.max_response_size
+    }
+
+    /// Configure the `Host` header to send instead of the endpoint host, or the endpoint host
+    /// if `None`
+    pub fn set_host_header(&mut self, host_header: Option<String>) {
+        self.host_header = host_header;
+    }
+
+    /// Get the `Host` header requests are sent and signed with
+    pub fn host_header(&self) -> String {
+        self.host_header.clone().unwrap_or_else(|| self.host())
+    }
+
+    /// Configure the callbacks invoked around every request, or none if `None`
+    pub fn set_metrics(&mut self, metrics: Option<Arc<dyn S3Metrics>>) {
+        self.metrics = metrics.map(Metrics);
+    }
+
+    /// Get the callbacks invoked around every request, if any
+    pub fn metrics(&self) -> Option<Arc<dyn S3Metrics>> {
+        self.metrics.as_ref().map(|metrics| metrics.0.clone())
+    }
+
+    /// Configure where the time requests are signed with comes from, or the system time if `None`
+    pub fn set_clock(&mut self, clock: Option<Arc<ClockFn>>) {
+        self.clock = clock.map(Clock);
+    }
+
+    /// Get the clock requests are signed with, if not the system time
+    pub fn clock(&self) -> Option<Arc<ClockFn>> {
+        self.clock.as_ref().map(|clock| clock.0.clone())
+    }
+
+    /// Configure bucket to sign requests rejected as too skewed again, as of the server's time
+    pub fn set_clock_skew_correction(&mut self, clock_skew_correction: bool) {
+        self.clock_skew_correction = clock_skew_correction;
+    }
+
+    /// Get clock_skew_correction field of the Bucket struct
+    pub fn is_clock_skew_correction(&self) -> bool {
+        self.clock_skew_correction
+    }
+
+    /// The current time, as told by the clock of the bucket, corrected for its skew from the
+    /// server's if enabled
+    pub(crate) fn now(&self) -> OffsetDateTime {
+        let now = self.uncorrected_now();
+        if self.clock_skew_correction {
+            now + time::Duration::seconds(self.clock_offset.load(Ordering::Relaxed))
+        } else {
+            now
+        }
+    }
+
+    fn uncorrected_now(&self) -> OffsetDateTime {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Make up for the clock being off from `server_time` in later requests.
+    pub(crate) fn correct_clock_skew(&self, server_time: OffsetDateTime) {
+        let offset = (server_time - self.uncorrected_now()).whole_seconds();
+        self.clock_offset.store(offset, Ordering::Relaxed);
+    }
+
     /// Configure bucket to use the older ListObjects API
     ///
     /// If your provider doesn't support the ListObjectsV2 interface, set this to
@@ -80,11 +227,54 @@ impl Bucket {
 
     /// Get a paths-style reference to the hostname of the S3 API endpoint.
     pub fn path_style_host(&self) -> String {
-        self.region.host()
+        self.endpoint_host()
     }
 
     pub fn subdomain_style_host(&self) -> String {
-        format!("{}.{}", self.name, self.region.host())
+        format!("{}.{}", self.name, self.endpoint_host())
+    }
+
+    /// Hostname of the S3 API endpoint, taking transfer acceleration and dual-stack into account.
+    fn endpoint_host(&self) -> String {
+        let host = self.region.host();
+        if self.transfer_acceleration {
+            if self.dualstack {
+                "s3-accelerate.dualstack.amazonaws.com".to_string()
+            } else {
+                "s3-accelerate.amazonaws.com".to_string()
+            }
+        } else if self.dualstack {
+            // e.g. s3.dualstack.eu-west-1.amazonaws.com or
+            // s3.dualstack.cn-north-1.amazonaws.com.cn
+            match ["amazonaws.com", "amazonaws.com.cn"]
+                .iter()
+                .find(|suffix| host.ends_with(&format!(".{}", suffix)))
+            {
+                Some(suffix) => format!("s3.dualstack.{}.{}", self.region, suffix),
+                None => host,
+            }
+        } else {
+            host
+        }
+    }
+
+    /// Transfer acceleration needs subdomain style requests and a bucket name that is a valid
+    /// DNS label, so no dots.
+    pub(crate) fn validate_transfer_acceleration(&self) -> Result<(), S3Error> {
+        if !self.transfer_acceleration {
+            return Ok(());
+        }
+        let name = self.name.as_bytes();
+        let dns_compatible = (3..=63).contains(&name.len())
+            && name
+                .iter()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'-')
+            && name[0] != b'-'
+            && name[name.len() - 1] != b'-';
+        if self.path_style || !dns_compatible {
+            return Err(S3Error::TransferAcceleration(self.name()));
+        }
+        Ok(())
     }
 
     // pub fn self_host(&self) -> String {
@@ -122,6 +312,21 @@ impl Bucket {
             .map(|key| key.replace('\n', "")))
     }
 
+    /// The access and secret key to sign requests with, or `None` for anonymous credentials,
+    /// with neither, whose requests are sent unsigned. Fails with
+    /// [`S3Error::MissingCredentials`] if only one of them is set, or either is empty.
+    pub(crate) fn signing_keys(&self) -> Result<Option<(String, String)>, S3Error> {
+        match (self.access_key()?, self.secret_key()?) {
+            (None, None) => Ok(None),
+            (Some(access_key), Some(secret_key))
+                if !access_key.is_empty() && !secret_key.is_empty() =>
+            {
+                Ok(Some((access_key, secret_key)))
+            }
+            _ => Err(S3Error::MissingCredentials),
+        }
+    }
+
     /// Get a reference to the AWS security token.
     pub fn security_token(&self) -> Result<Option<String>, S3Error> {
         Ok(self
@@ -201,6 +406,10 @@ impl Bucket {
         self.request_timeout
     }
 
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     /// Get Bucket location.
     ///
     /// # Example:
@@ -224,14 +433,10 @@ impl Bucket {
     /// # }
     /// ```
     pub async fn location(&self) -> Result<(Region, u16), S3Error> {
-        let request = RequestImpl::new(self, "?location", Command::GetBucketLocation)?;
+        let request = RequestImpl::new(self, "/", Command::GetBucketLocation)?;
         let response_data = request.response_data(false).await?;
-        let region_string = String::from_utf8_lossy(response_data.as_slice());
-        let region = match quick_xml::de::from_reader(region_string.as_bytes()) {
-            Ok(r) => {
-                let location_result: BucketLocationResult = r;
-                location_result.region.parse()?
-            }
+        let region = match response_data.deserialize_xml::<BucketLocationResult>() {
+            Ok(location_result) => location_result.region.parse()?,
             Err(e) => {
                 if response_data.status_code() == 200 {
                     Region::Custom {
@@ -1,4 +1,5 @@
 use crate::error::S3Error;
+use crate::serde_types::AccessControlPolicy;
 use crate::Region;
 
 /// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/dev/acl-overview.html#CannedACL)
@@ -12,9 +13,13 @@ pub enum CannedBucketAcl {
     Custom(String),
 }
 
+use crate::utils::encode_metadata_value;
 use http::header::HeaderName;
 use http::HeaderMap;
+use std::collections::HashMap;
 use std::fmt;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
 
 impl fmt::Display for CannedBucketAcl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -28,6 +33,238 @@ impl fmt::Display for CannedBucketAcl {
     }
 }
 
+/// ACL to set with [`Bucket::put_bucket_acl`](crate::Bucket::put_bucket_acl), either a canned
+/// one or a full access control policy.
+#[derive(Clone, Debug)]
+pub enum AclConfiguration {
+    Canned(CannedBucketAcl),
+    Policy(AccessControlPolicy),
+}
+
+impl From<CannedBucketAcl> for AclConfiguration {
+    fn from(acl: CannedBucketAcl) -> Self {
+        AclConfiguration::Canned(acl)
+    }
+}
+
+impl From<AccessControlPolicy> for AclConfiguration {
+    fn from(policy: AccessControlPolicy) -> Self {
+        AclConfiguration::Policy(policy)
+    }
+}
+
+/// Optional settings of an object uploaded with
+/// [`Bucket::put_object_with_options`](crate::Bucket::put_object_with_options), sent as request
+/// headers.
+#[derive(Clone, Debug, Default)]
+pub struct PutObjectOptions {
+    /// When the bucket is served with static website hosting, redirect requests for the object
+    /// to another object in the bucket, e.g. `/new-page.html`, or to an external URL
+    pub website_redirect_location: Option<String>,
+    /// Only replace the object if its current ETag is this one, sent as `If-Match`
+    pub if_match: Option<String>,
+    /// Only replace the object if it didn't change since then, sent as `If-Unmodified-Since`
+    pub if_unmodified_since: Option<OffsetDateTime>,
+    /// User metadata, sent as `x-amz-meta-<name>` headers. Values that aren't printable ASCII
+    /// are encoded, see [`encode_metadata_value`](crate::utils::encode_metadata_value)
+    pub metadata: HashMap<String, String>,
+    /// Grantees given read, read ACP and write ACP permissions, sent as
+    /// `x-amz-grant-full-control`
+    pub grant_full_control: Vec<BucketAcl>,
+    /// Grantees allowed to read the object and its metadata, sent as `x-amz-grant-read`
+    pub grant_read: Vec<BucketAcl>,
+    /// Grantees allowed to read the object ACL, sent as `x-amz-grant-read-acp`
+    pub grant_read_acp: Vec<BucketAcl>,
+    /// Grantees allowed to write the object ACL, sent as `x-amz-grant-write-acp`
+    pub grant_write_acp: Vec<BucketAcl>,
+}
+
+impl PutObjectOptions {
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<(), S3Error> {
+        if let Some(website_redirect_location) = &self.website_redirect_location {
+            headers.insert(
+                HeaderName::from_static("x-amz-website-redirect-location"),
+                website_redirect_location.parse()?,
+            );
+        }
+        if let Some(if_match) = &self.if_match {
+            headers.insert(http::header::IF_MATCH, if_match.parse()?);
+        }
+        if let Some(if_unmodified_since) = self.if_unmodified_since {
+            headers.insert(
+                http::header::IF_UNMODIFIED_SINCE,
+                if_unmodified_since.format(&Rfc2822)?.parse()?,
+            );
+        }
+        for (name, value) in &self.metadata {
+            headers.insert(
+                HeaderName::from_bytes(format!("x-amz-meta-{}", name).as_bytes())?,
+                encode_metadata_value(value).parse()?,
+            );
+        }
+        for (name, grantees) in [
+            ("x-amz-grant-full-control", &self.grant_full_control),
+            ("x-amz-grant-read", &self.grant_read),
+            ("x-amz-grant-read-acp", &self.grant_read_acp),
+            ("x-amz-grant-write-acp", &self.grant_write_acp),
+        ] {
+            if !grantees.is_empty() {
+                headers.insert(HeaderName::from_static(name), acl_list(grantees).parse()?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Optional settings of an object download with
+/// [`Bucket::get_object_with_options`](crate::Bucket::get_object_with_options), sent as request
+/// headers.
+#[derive(Clone, Debug, Default)]
+pub struct GetObjectOptions {
+    /// Sent as `Cache-Control`, e.g. `no-cache` for caches in front of the bucket, like
+    /// CloudFront, to fetch the object from the bucket again instead of serving a stored copy
+    pub cache_control: Option<String>,
+    /// Only download the object if its current ETag is this one, sent as `If-Match`
+    pub if_match: Option<String>,
+    /// Have S3 return the checksums stored with the object, sent as
+    /// `x-amz-checksum-mode: ENABLED`. The downloaded object is then checked against them,
+    /// see [`ResponseData::checksum`](crate::request::ResponseData::checksum)
+    pub checksum_mode: bool,
+    /// Download only the bytes from the first to the last position, both inclusive, to the
+    /// end of the object if there's none, sent as `Range`
+    pub range: Option<(u64, Option<u64>)>,
+}
+
+impl GetObjectOptions {
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<(), S3Error> {
+        if let Some(cache_control) = &self.cache_control {
+            headers.insert(http::header::CACHE_CONTROL, cache_control.parse()?);
+        }
+        if let Some(if_match) = &self.if_match {
+            headers.insert(http::header::IF_MATCH, if_match.parse()?);
+        }
+        if self.checksum_mode {
+            headers.insert(
+                HeaderName::from_static("x-amz-checksum-mode"),
+                "ENABLED".parse()?,
+            );
+        }
+        if let Some((start, end)) = self.range {
+            let range = match end {
+                Some(end) => format!("bytes={}-{}", start, end),
+                None => format!("bytes={}-", start),
+            };
+            headers.insert(http::header::RANGE, range.parse()?);
+        }
+        Ok(())
+    }
+}
+
+/// Conditions on the source object of a copy, see
+/// [`Bucket::copy_object_internal_with_conditions`](crate::Bucket::copy_object_internal_with_conditions).
+/// The copy fails with [`S3Error::PreconditionFailed`] unless all of them hold.
+#[derive(Clone, Debug, Default)]
+pub struct CopyConditions {
+    /// Copy only if the source's ETag is this one, `x-amz-copy-source-if-match`
+    pub if_match: Option<String>,
+    /// Copy only if the source's ETag isn't this one, `x-amz-copy-source-if-none-match`
+    pub if_none_match: Option<String>,
+    /// Copy only if the source changed since then, `x-amz-copy-source-if-modified-since`
+    pub if_modified_since: Option<OffsetDateTime>,
+    /// Copy only if the source didn't change since then, `x-amz-copy-source-if-unmodified-since`
+    pub if_unmodified_since: Option<OffsetDateTime>,
+}
+
+impl CopyConditions {
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<(), S3Error> {
+        if let Some(if_match) = &self.if_match {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-match"),
+                if_match.parse()?,
+            );
+        }
+        if let Some(if_none_match) = &self.if_none_match {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-none-match"),
+                if_none_match.parse()?,
+            );
+        }
+        if let Some(if_modified_since) = self.if_modified_since {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-modified-since"),
+                if_modified_since.format(&Rfc2822)?.parse()?,
+            );
+        }
+        if let Some(if_unmodified_since) = self.if_unmodified_since {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-unmodified-since"),
+                if_unmodified_since.format(&Rfc2822)?.parse()?,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Server side encryption to request for an uploaded object.
+#[derive(Clone, Debug)]
+pub enum ServerSideEncryption {
+    /// Keys managed by S3, `AES256`
+    Aes256,
+    /// Keys managed by KMS, `aws:kms`, with the bucket's default key when `key_id` is `None`
+    AwsKms { key_id: Option<String> },
+}
+
+impl ServerSideEncryption {
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<(), S3Error> {
+        let algorithm = match self {
+            ServerSideEncryption::Aes256 => "AES256",
+            ServerSideEncryption::AwsKms { .. } => "aws:kms",
+        };
+        headers.insert(
+            HeaderName::from_static("x-amz-server-side-encryption"),
+            algorithm.parse()?,
+        );
+        if let ServerSideEncryption::AwsKms {
+            key_id: Some(key_id),
+        } = self
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-server-side-encryption-aws-kms-key-id"),
+                key_id.parse()?,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Settings of an upload through a URL presigned with
+/// [`Bucket::presign_put_with_options`](crate::Bucket::presign_put_with_options).
+///
+/// They become signed headers, the client has to send them along with the exact same values
+/// for the upload to be accepted.
+#[derive(Clone, Debug, Default)]
+pub struct PresignPutOptions {
+    /// Canned ACL, sent as `x-amz-acl`
+    pub acl: Option<CannedBucketAcl>,
+    /// Sent as `x-amz-server-side-encryption`
+    pub server_side_encryption: Option<ServerSideEncryption>,
+}
+
+impl PresignPutOptions {
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<(), S3Error> {
+        if let Some(acl) = &self.acl {
+            headers.insert(
+                HeaderName::from_static("x-amz-acl"),
+                acl.to_string().parse()?,
+            );
+        }
+        if let Some(server_side_encryption) = &self.server_side_encryption {
+            server_side_encryption.add_headers(headers)?;
+        }
+        Ok(())
+    }
+}
+
 /// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/dev/acl-overview.html)
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
@@ -42,7 +279,7 @@ impl fmt::Display for BucketAcl {
         match self {
             BucketAcl::Id { id } => write!(f, "id=\"{}\"", id),
             BucketAcl::Uri { uri } => write!(f, "uri=\"{}\"", uri),
-            BucketAcl::Email { email } => write!(f, "email=\"{}\"", email),
+            BucketAcl::Email { email } => write!(f, "emailAddress=\"{}\"", email),
         }
     }
 }
@@ -130,9 +367,13 @@ impl BucketConfiguration {
         self.location_constraint = Some(region)
     }
 
+    /// The `CreateBucketConfiguration` body, omitted for `us-east-1` (including custom regions
+    /// by that name) where AWS rejects any location constraint with `InvalidLocationConstraint`.
     pub fn location_constraint_payload(&self) -> Option<String> {
         if let Some(ref location_constraint) = self.location_constraint {
-            if location_constraint == &Region::UsEast1 {
+            if location_constraint == &Region::UsEast1
+                || location_constraint.to_string() == Region::UsEast1.to_string()
+            {
                 return None;
             }
             Some(format!(
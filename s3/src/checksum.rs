@@ -0,0 +1,133 @@
+//! Additional checksums S3 verifies the payload of an upload against
+
+use std::fmt;
+
+/// Algorithm of an additional checksum, beside the MD5 based ETag.
+///
+/// The checksum is sent base64 encoded in the header named by
+/// [`header_name`](ChecksumAlgorithm::header_name).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+}
+
+impl ChecksumAlgorithm {
+    /// Every algorithm supported.
+    pub(crate) const ALL: &'static [ChecksumAlgorithm] = &[ChecksumAlgorithm::Crc32];
+
+    /// Name of the header carrying the checksum, e.g. `x-amz-checksum-crc32`.
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+        }
+    }
+
+    /// Base64 encoded checksum of `data`, as S3 expects it.
+    pub fn compute(&self, data: &[u8]) -> String {
+        let mut checksum = self.hasher();
+        checksum.update(data);
+        checksum.finalize()
+    }
+
+    /// Checksum of an object uploaded in parts, from the base64 encoded checksums of its parts
+    /// in order: the checksum of the concatenated part checksums, followed by `-` and the
+    /// number of parts, the way S3 reports it. `None` if one of them isn't valid base64.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use s3::checksum::ChecksumAlgorithm;
+    ///
+    /// let algorithm = ChecksumAlgorithm::Crc32;
+    /// let parts = [algorithm.compute(b"hello "), algorithm.compute(b"world")];
+    /// let checksum = algorithm.composite(&parts).unwrap();
+    /// assert!(checksum.ends_with("-2"));
+    /// ```
+    pub fn composite<S: AsRef<str>>(&self, part_checksums: &[S]) -> Option<String> {
+        use base64::engine::general_purpose;
+        use base64::Engine;
+
+        let mut checksum = self.hasher();
+        for part_checksum in part_checksums {
+            checksum.update(
+                &general_purpose::STANDARD
+                    .decode(part_checksum.as_ref())
+                    .ok()?,
+            );
+        }
+        Some(format!("{}-{}", checksum.finalize(), part_checksums.len()))
+    }
+
+    pub(crate) fn hasher(&self) -> Checksum {
+        match self {
+            ChecksumAlgorithm::Crc32 => Checksum::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumAlgorithm::Crc32 => write!(f, "CRC32"),
+        }
+    }
+}
+
+/// Checksum computed incrementally, for payloads that are streamed.
+pub(crate) enum Checksum {
+    Crc32(crc32fast::Hasher),
+}
+
+impl Checksum {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Checksum::Crc32(hasher) => hasher.update(data),
+        }
+    }
+
+    /// The base64 encoded checksum of everything passed to [`update`](Checksum::update).
+    pub fn finalize(self) -> String {
+        use base64::engine::general_purpose;
+        use base64::Engine;
+
+        let digest = match self {
+            Checksum::Crc32(hasher) => hasher.finalize().to_be_bytes(),
+        };
+        general_purpose::STANDARD.encode(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChecksumAlgorithm;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(ChecksumAlgorithm::Crc32.compute(b"hello world"), "DUoRhQ==");
+        assert_eq!(ChecksumAlgorithm::Crc32.compute(b""), "AAAAAA==");
+
+        let mut checksum = ChecksumAlgorithm::Crc32.hasher();
+        checksum.update(b"hello ");
+        checksum.update(b"world");
+        assert_eq!(checksum.finalize(), "DUoRhQ==");
+    }
+
+    #[test]
+    fn test_crc32_composite() {
+        let algorithm = ChecksumAlgorithm::Crc32;
+        let parts = [algorithm.compute(b"hello "), algorithm.compute(b"world")];
+        let mut raw = Vec::new();
+        raw.extend(crc32fast::hash(b"hello ").to_be_bytes());
+        raw.extend(crc32fast::hash(b"world").to_be_bytes());
+        assert_eq!(
+            algorithm.composite(&parts),
+            Some(format!("{}-2", algorithm.compute(&raw)))
+        );
+        // Not the checksum of the whole content
+        assert_ne!(
+            algorithm.composite(&parts),
+            Some(format!("{}-2", algorithm.compute(b"hello world")))
+        );
+        assert_eq!(algorithm.composite(&["not base64!"]), None);
+    }
+}
@@ -0,0 +1,24 @@
+//! Overriding the time requests are signed with
+
+use std::fmt;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+/// Source of the current time, see [`Bucket::with_clock`](crate::Bucket::with_clock).
+pub type ClockFn = dyn Fn() -> OffsetDateTime + Send + Sync;
+
+/// The clock of a bucket.
+#[derive(Clone)]
+pub(crate) struct Clock(pub Arc<ClockFn>);
+
+impl Clock {
+    pub fn now(&self) -> OffsetDateTime {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
@@ -1,10 +1,18 @@
 use std::collections::HashMap;
 
-use crate::serde_types::{CompleteMultipartUploadData, CorsConfiguration};
+use crate::bucket_ops::{AclConfiguration, CopyConditions, GetObjectOptions, PutObjectOptions};
+use crate::checksum::ChecksumAlgorithm;
+use crate::serde_types::{
+    AccelerateConfiguration, CompleteMultipartUploadData, CorsConfiguration, DeleteObjectsRequest,
+    NotificationConfiguration, ObjectAttribute, OwnershipControls,
+    ServerSideEncryptionConfiguration,
+};
 
-use crate::EMPTY_PAYLOAD_SHA;
+use crate::{EMPTY_PAYLOAD_SHA, STREAMING_UNSIGNED_PAYLOAD_TRAILER, UNSIGNED_PAYLOAD};
 use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HttpMethod {
     Delete,
     Get,
@@ -51,25 +59,94 @@ impl<'a> Multipart<'a> {
     }
 }
 
+/// Bucket configurations stored in their own subresource under an id, of which a bucket can
+/// have several.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigurationKind {
+    Inventory,
+    Analytics,
+    Metrics,
+}
+
+impl ConfigurationKind {
+    pub fn subresource(&self) -> &'static str {
+        match self {
+            ConfigurationKind::Inventory => "inventory",
+            ConfigurationKind::Analytics => "analytics",
+            ConfigurationKind::Metrics => "metrics",
+        }
+    }
+}
+
 #[derive(Clone, Debug, strum_macros::Display)]
 pub enum Command<'a> {
     HeadObject,
+    HeadObjectVersion {
+        version_id: &'a str,
+    },
+    /// HeadObject with the query parameters `queries`, e.g. `versionId` or the
+    /// `response-*` overrides of a presigned GET
+    HeadObjectWithQuery {
+        queries: &'a HashMap<String, String>,
+    },
+    /// HeadObject answered with `304 Not Modified` unless the object changed `since`
+    HeadObjectIfModifiedSince {
+        since: OffsetDateTime,
+    },
     CopyObject {
         from: &'a str,
+        conditions: Option<&'a CopyConditions>,
     },
     DeleteObject,
+    /// DeleteObject failing with `412 Precondition Failed` unless the object's ETag is `etag`
+    DeleteObjectIfMatch {
+        etag: &'a str,
+    },
     DeleteObjectTagging,
     GetObject,
+    /// GetObject answered with `response_content_type` as its `Content-Type`
+    GetObjectAs {
+        response_content_type: &'a str,
+    },
+    /// GetObject sending the request headers of `options`
+    GetObjectWithOptions {
+        options: &'a GetObjectOptions,
+    },
     GetObjectTorrent,
     GetObjectRange {
         start: u64,
         end: Option<u64>,
     },
+    /// GetObject of several inclusive byte ranges in one request, answered with a
+    /// `multipart/byteranges` body by servers supporting it
+    GetObjectRanges {
+        ranges: &'a [(u64, Option<u64>)],
+    },
+    GetObjectPart {
+        part_number: u32,
+    },
     GetObjectTagging,
+    GetObjectAttributes {
+        attributes: &'a [ObjectAttribute],
+        /// List the object parts after this part number
+        part_number_marker: Option<u32>,
+    },
     PutObject {
         content: &'a [u8],
         content_type: &'a str,
         multipart: Option<Multipart<'a>>,
+        options: Option<&'a PutObjectOptions>,
+        /// Hex SHA256 of `content`, computed up front, to sign with instead of hashing it
+        sha256: Option<&'a str>,
+    },
+    /// PutObject whose body is streamed from a reader of unknown length, so
+    /// the payload is sent unsigned and without a `Content-Length`.
+    PutObjectReader {
+        content_type: &'a str,
+        /// Sent as `Content-Length` when known, chunked transfer encoding is used otherwise
+        content_length: Option<u64>,
+        /// Checksum of the payload to send in a trailer after it
+        checksum: Option<ChecksumAlgorithm>,
     },
     PutObjectTagging {
         tags: &'a str,
@@ -78,6 +155,7 @@ pub enum Command<'a> {
         prefix: Option<&'a str>,
         delimiter: Option<&'a str>,
         key_marker: Option<String>,
+        upload_id_marker: Option<String>,
         max_uploads: Option<usize>,
     },
     ListObjects {
@@ -109,6 +187,20 @@ pub enum Command<'a> {
     PresignDelete {
         expiry_secs: u32,
     },
+    PresignInitiateMultipartUpload {
+        expiry_secs: u32,
+        content_type: &'a str,
+    },
+    PresignCompleteMultipartUpload {
+        expiry_secs: u32,
+        upload_id: &'a str,
+    },
+    Presign {
+        method: HttpMethod,
+        expiry_secs: u32,
+        custom_headers: Option<HeaderMap>,
+        custom_queries: Option<HashMap<String, String>>,
+    },
     InitiateMultipartUpload {
         content_type: &'a str,
     },
@@ -132,44 +224,161 @@ pub enum Command<'a> {
     PutBucketCors {
         configuration: CorsConfiguration,
     },
+    PutBucketEncryption {
+        configuration: ServerSideEncryptionConfiguration,
+    },
+    GetBucketEncryption,
+    DeleteBucketEncryption,
+    PutBucketAccelerateConfiguration {
+        configuration: AccelerateConfiguration,
+    },
+    GetBucketAccelerateConfiguration,
+    GetBucketAcl,
+    PutBucketAcl {
+        acl: AclConfiguration,
+    },
+    PutObjectAcl {
+        acl: AclConfiguration,
+    },
+    GetBucketOwnershipControls,
+    PutBucketOwnershipControls {
+        configuration: OwnershipControls,
+    },
+    GetBucketNotificationConfiguration,
+    DeleteObjects {
+        delete: DeleteObjectsRequest,
+    },
+    PutBucketNotificationConfiguration {
+        configuration: NotificationConfiguration,
+    },
+    PutBucketConfiguration {
+        kind: ConfigurationKind,
+        id: &'a str,
+        configuration: String,
+    },
+    GetBucketConfiguration {
+        kind: ConfigurationKind,
+        id: &'a str,
+    },
+    DeleteBucketConfiguration {
+        kind: ConfigurationKind,
+        id: &'a str,
+    },
+    ListBucketConfigurations {
+        kind: ConfigurationKind,
+        continuation_token: Option<&'a str>,
+    },
 }
 
 impl<'a> Command<'a> {
     pub fn http_verb(&self) -> HttpMethod {
         match *self {
             Command::GetObject
+            | Command::GetObjectAs { .. }
+            | Command::GetObjectWithOptions { .. }
             | Command::GetObjectTorrent
             | Command::GetObjectRange { .. }
+            | Command::GetObjectRanges { .. }
+            | Command::GetObjectPart { .. }
             | Command::ListBuckets
             | Command::ListObjects { .. }
             | Command::ListObjectsV2 { .. }
             | Command::GetBucketLocation
             | Command::GetObjectTagging
+            | Command::GetObjectAttributes { .. }
             | Command::ListMultipartUploads { .. }
+            | Command::GetBucketEncryption
+            | Command::GetBucketAccelerateConfiguration
+            | Command::GetBucketAcl
+            | Command::GetBucketOwnershipControls
+            | Command::GetBucketNotificationConfiguration
+            | Command::GetBucketConfiguration { .. }
+            | Command::ListBucketConfigurations { .. }
             | Command::PresignGet { .. } => HttpMethod::Get,
             Command::PutObject { .. }
-            | Command::CopyObject { from: _ }
+            | Command::PutObjectReader { .. }
+            | Command::CopyObject { .. }
             | Command::PutObjectTagging { .. }
             | Command::PresignPut { .. }
             | Command::UploadPart { .. }
             | Command::PutBucketCors { .. }
+            | Command::PutBucketEncryption { .. }
+            | Command::PutBucketAccelerateConfiguration { .. }
+            | Command::PutBucketAcl { .. }
+            | Command::PutObjectAcl { .. }
+            | Command::PutBucketOwnershipControls { .. }
+            | Command::PutBucketNotificationConfiguration { .. }
+            | Command::PutBucketConfiguration { .. }
             | Command::CreateBucket { .. } => HttpMethod::Put,
             Command::DeleteObject
+            | Command::DeleteObjectIfMatch { .. }
             | Command::DeleteObjectTagging
             | Command::AbortMultipartUpload { .. }
             | Command::PresignDelete { .. }
+            | Command::DeleteBucketEncryption
+            | Command::DeleteBucketConfiguration { .. }
             | Command::DeleteBucket => HttpMethod::Delete,
-            Command::InitiateMultipartUpload { .. } | Command::CompleteMultipartUpload { .. } => {
-                HttpMethod::Post
+            Command::InitiateMultipartUpload { .. }
+            | Command::CompleteMultipartUpload { .. }
+            | Command::DeleteObjects { .. } => HttpMethod::Post,
+            Command::HeadObject
+            | Command::HeadObjectVersion { .. }
+            | Command::HeadObjectWithQuery { .. }
+            | Command::HeadObjectIfModifiedSince { .. } => HttpMethod::Head,
+            Command::Presign { method, .. } => method,
+            Command::PresignPost { .. }
+            | Command::PresignInitiateMultipartUpload { .. }
+            | Command::PresignCompleteMultipartUpload { .. } => HttpMethod::Post,
+        }
+    }
+
+    /// Whether a response with this non-2xx `status` is an expected outcome of the command,
+    /// handled by its caller, rather than a failure. The backend passes such responses through
+    /// even with the `fail-on-err` feature.
+    pub fn expects_status(&self, status: u16) -> bool {
+        match self {
+            // Missing objects and unmet preconditions are answers to a HEAD, not errors
+            Command::HeadObject
+            | Command::HeadObjectVersion { .. }
+            | Command::HeadObjectWithQuery { .. }
+            | Command::HeadObjectIfModifiedSince { .. } => {
+                matches!(status, 304 | 404 | 412)
             }
-            Command::HeadObject => HttpMethod::Head,
-            Command::PresignPost { .. } => HttpMethod::Post,
+            _ => false,
+        }
+    }
+
+    /// XML body of the commands sending one, like those putting a bucket level configuration.
+    pub fn configuration_payload(&self) -> Option<String> {
+        match self {
+            Command::PutBucketEncryption { configuration } => Some(configuration.to_string()),
+            Command::PutBucketAccelerateConfiguration { configuration } => {
+                Some(configuration.to_string())
+            }
+            Command::PutBucketAcl {
+                acl: AclConfiguration::Policy(policy),
+            }
+            | Command::PutObjectAcl {
+                acl: AclConfiguration::Policy(policy),
+            } => Some(policy.to_string()),
+            Command::PutBucketOwnershipControls { configuration } => {
+                Some(configuration.to_string())
+            }
+            Command::PutBucketNotificationConfiguration { configuration } => {
+                Some(configuration.to_string())
+            }
+            Command::PutBucketConfiguration { configuration, .. } => Some(configuration.clone()),
+            Command::DeleteObjects { delete } => Some(delete.to_string()),
+            _ => None,
         }
     }
 
     pub fn content_length(&self) -> usize {
+        if let Some(payload) = self.configuration_payload() {
+            return payload.len();
+        }
         match &self {
-            Command::CopyObject { from: _ } => 0,
+            Command::CopyObject { .. } => 0,
             Command::PutObject { content, .. } => content.len(),
             Command::PutObjectTagging { tags } => tags.len(),
             Command::UploadPart { content, .. } => content.len(),
@@ -189,13 +398,24 @@ impl<'a> Command<'a> {
         match self {
             Command::InitiateMultipartUpload { content_type } => content_type.to_string(),
             Command::PutObject { content_type, .. } => content_type.to_string(),
+            Command::PutObjectReader { content_type, .. } => content_type.to_string(),
             Command::CompleteMultipartUpload { .. } => "application/xml".into(),
+            _ if self.configuration_payload().is_some() => "application/xml".into(),
             _ => "text/plain".into(),
         }
     }
 
     pub fn sha256(&self) -> String {
+        if let Some(payload) = self.configuration_payload() {
+            let mut sha = Sha256::default();
+            sha.update(payload.as_bytes());
+            return hex::encode(sha.finalize().as_slice());
+        }
         match &self {
+            Command::PutObject {
+                sha256: Some(sha256),
+                ..
+            } => sha256.to_string(),
             Command::PutObject { content, .. } => {
                 let mut sha = Sha256::default();
                 sha.update(content);
@@ -220,6 +440,8 @@ impl<'a> Command<'a> {
                     EMPTY_PAYLOAD_SHA.into()
                 }
             }
+            Command::PutObjectReader { checksum: None, .. } => UNSIGNED_PAYLOAD.into(),
+            Command::PutObjectReader { .. } => STREAMING_UNSIGNED_PAYLOAD_TRAILER.into(),
             _ => EMPTY_PAYLOAD_SHA.into(),
         }
     }
@@ -6,10 +6,70 @@ pub enum S3Error {
     Utf8(#[from] std::str::Utf8Error),
     #[error("Max expiration for presigned URLs is one week, or 604.800 seconds, got {0} instead")]
     MaxExpiry(u32),
+    #[error("Min expiration for presigned URLs is one second, got {0} instead")]
+    MinExpiry(u32),
+    #[error("Presigned URL would be valid for {expiry_secs} seconds, but the credentials signing it expire in {remaining_secs}")]
+    ExpiryExceedsCredentials {
+        expiry_secs: u32,
+        remaining_secs: i64,
+    },
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("Part {part_number} is {size} bytes, all parts but the last need at least 5 MiB")]
+    InvalidPartSize { part_number: u32, size: usize },
     #[error("Got HTTP {0} with content '{1}'")]
-    HttpFailWithBody(u16, String),
+    HttpFailWithBody(u16, String, Box<http::HeaderMap>),
+    #[error("No such key: {key}")]
+    NoSuchKey {
+        key: String,
+        /// The current version of the key is a delete marker
+        delete_marker: bool,
+        /// `x-amz-request-id` of the response
+        request_id: Option<String>,
+        /// `x-amz-id-2` of the response
+        extended_request_id: Option<String>,
+    },
+    #[error("Precondition failed, the current ETag is {etag:?}")]
+    PreconditionFailed {
+        etag: Option<String>,
+        /// `x-amz-request-id` of the response
+        request_id: Option<String>,
+        /// `x-amz-id-2` of the response
+        extended_request_id: Option<String>,
+    },
+    #[error("ACLs are disabled on the bucket, its object ownership is BucketOwnerEnforced, see Bucket::put_bucket_ownership_controls")]
+    AclNotSupported {
+        /// `x-amz-request-id` of the response
+        request_id: Option<String>,
+        /// `x-amz-id-2` of the response
+        extended_request_id: Option<String>,
+    },
+    #[error("Transfer acceleration needs subdomain style requests and a DNS compatible bucket name without dots, got '{0}'")]
+    TransferAcceleration(String),
+    #[error("Multipart threshold of {0} bytes is below the minimum part size of 5 MiB")]
+    MultipartThreshold(usize),
+    #[error("Size of {0} unknown, neither HEAD nor GetObjectAttributes returned it")]
+    UnknownObjectSize(String),
+    #[error("Expected a SHA256 as 64 lowercase hex digits, got '{0}'")]
+    InvalidSha256(String),
+    #[error("{algorithm} checksum of the downloaded content is {computed}, S3 stored {expected}")]
+    ChecksumMismatch {
+        algorithm: crate::checksum::ChecksumAlgorithm,
+        expected: String,
+        computed: String,
+    },
+    #[error("Response body is larger than the limit of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    #[error("Server did not honor the requested range: {0}")]
+    RangeNotHonored(String),
+    #[error("Invalid byte range: {0}")]
+    InvalidRange(String),
+    #[error("Expected an s3:// URL or the URL of an S3 bucket or object, got '{0}'")]
+    InvalidS3Url(String),
     #[error("Http request returned a non 2** code")]
     HttpFail,
+    #[error("No response within the request timeout of {0:?}")]
+    RequestTimeout(std::time::Duration),
     #[error("aws-creds: {0}")]
     Credentials(#[from] crate::creds::error::CredentialsError),
     #[error("aws-region: {0}")]
@@ -30,16 +90,124 @@ pub enum S3Error {
     FromUtf8(#[from] std::string::FromUtf8Error),
     #[error("serde xml: {0}")]
     SerdeXml(#[from] quick_xml::de::DeError),
+    #[error("serde json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
     #[error("invalid header value: {0}")]
     InvalidHeaderValue(#[from] http::header::InvalidHeaderValue),
     #[error("invalid header name: {0}")]
     InvalidHeaderName(#[from] http::header::InvalidHeaderName),
+    #[error("Credentials need both an access key and a secret key to sign requests")]
+    MissingCredentials,
     #[error("Could not get Write lock on Credentials")]
     WLCredentials,
     #[error("Could not get Read lock on Credentials")]
     RLCredentials,
     #[error("Time format error: {0}")]
     TimeFormatError(#[from] time::error::Format),
+    #[error("Time parse error: {0}")]
+    TimeParseError(#[from] time::error::Parse),
     #[error("fmt error: {0}")]
     FmtError(#[from] std::fmt::Error),
 }
+
+impl S3Error {
+    /// Status code of the failed response, if the error originates from one.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            S3Error::HttpFailWithBody(status_code, ..) => Some(*status_code),
+            S3Error::NoSuchKey { .. } => Some(404),
+            S3Error::PreconditionFailed { .. } => Some(412),
+            S3Error::AclNotSupported { .. } => Some(400),
+            _ => None,
+        }
+    }
+
+    /// Headers of the failed response, if the error originates from one.
+    pub fn headers(&self) -> Option<&http::HeaderMap> {
+        match self {
+            S3Error::HttpFailWithBody(_, _, headers) => Some(headers),
+            _ => None,
+        }
+    }
+
+    /// Whether the request failed on a delete marker, like a GetObject on a key whose current
+    /// version is a delete marker, which S3 answers with a 404, or a 405 when a version was
+    /// asked for.
+    pub fn is_delete_marker(&self) -> bool {
+        match self {
+            S3Error::NoSuchKey { delete_marker, .. } => *delete_marker,
+            _ => self.header("x-amz-delete-marker") == Some("true"),
+        }
+    }
+
+    /// The `x-amz-request-id` of the failed request, useful when contacting support.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            S3Error::NoSuchKey { request_id, .. }
+            | S3Error::PreconditionFailed { request_id, .. }
+            | S3Error::AclNotSupported { request_id, .. } => request_id.as_deref(),
+            _ => self.header("x-amz-request-id"),
+        }
+    }
+
+    /// The `x-amz-id-2` (extended request id) of the failed request.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        match self {
+            S3Error::NoSuchKey {
+                extended_request_id,
+                ..
+            }
+            | S3Error::PreconditionFailed {
+                extended_request_id,
+                ..
+            }
+            | S3Error::AclNotSupported {
+                extended_request_id,
+                ..
+            } => extended_request_id.as_deref(),
+            _ => self.header("x-amz-id-2"),
+        }
+    }
+
+    /// How long the server asked to wait before retrying, from the `Retry-After` header.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        crate::retry::parse_retry_after(
+            self.header("retry-after")?,
+            time::OffsetDateTime::now_utc(),
+        )
+    }
+
+    /// The `Code` from the XML error body S3 sends along with a failure.
+    pub fn code(&self) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct ErrorCode {
+            #[serde(rename = "Code")]
+            code: String,
+        }
+
+        match self {
+            S3Error::HttpFailWithBody(_, body, _) => quick_xml::de::from_str::<ErrorCode>(body)
+                .ok()
+                .map(|error| error.code),
+            _ => None,
+        }
+    }
+
+    /// Reinterpret a `404 NoSuchKey` failure to fetch `key` as [`S3Error::NoSuchKey`].
+    pub(crate) fn for_key(self, key: &str) -> S3Error {
+        if self.status_code() == Some(404) && self.code().as_deref() == Some("NoSuchKey") {
+            S3Error::NoSuchKey {
+                key: key.to_string(),
+                delete_marker: self.is_delete_marker(),
+                request_id: self.request_id().map(str::to_string),
+                extended_request_id: self.extended_request_id().map(str::to_string),
+            }
+        } else {
+            self
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers()?.get(name)?.to_str().ok()
+    }
+}
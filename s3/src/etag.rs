@@ -0,0 +1,80 @@
+//! Computing the ETag S3 assigns to an object, to compare local data against a remote object
+//! without downloading it
+
+/// Compute the ETag S3 reports for `data` uploaded in parts of `chunk_size` bytes.
+///
+/// Data no larger than `chunk_size` is assumed to have been uploaded with a single PutObject,
+/// which makes the ETag the hex MD5 of the data. Larger data gets the multipart ETag: the MD5
+/// of the concatenated binary MD5s of every part, followed by `-` and the number of parts.
+///
+/// ETags of objects encrypted with SSE-KMS or SSE-C are not MD5 based and won't match.
+///
+/// # Example
+///
+/// ```no_run
+/// use s3::bucket::{Bucket, CHUNK_SIZE};
+/// use s3::creds::Credentials;
+/// use s3::etag::compute_etag;
+/// use anyhow::Result;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+/// let content = std::fs::read("test.file")?;
+///
+/// let (head_object_result, _) = bucket.head_object("/test.file").await?;
+/// let etag = head_object_result.e_tag.unwrap_or_default();
+/// let unchanged = etag.trim_matches('"') == compute_etag(&content, CHUNK_SIZE);
+/// # Ok(())
+/// # }
+/// ```
+pub fn compute_etag(data: &[u8], chunk_size: usize) -> String {
+    assert!(chunk_size > 0, "chunk_size must not be zero");
+
+    if data.len() <= chunk_size {
+        return format!("{:x}", md5::compute(data));
+    }
+
+    let mut digests = Vec::with_capacity((data.len() / chunk_size + 1) * 16);
+    let mut parts = 0;
+    for part in data.chunks(chunk_size) {
+        digests.extend_from_slice(&md5::compute(part).0);
+        parts += 1;
+    }
+    format!("{:x}-{}", md5::compute(digests), parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compute_etag;
+
+    const MB: usize = 1024 * 1024;
+
+    #[test]
+    fn test_compute_etag_single_part() {
+        assert_eq!(
+            compute_etag(b"I want to go to S3", 5 * MB),
+            "ba20b2035239232547699d0e78f76f4a"
+        );
+        assert_eq!(
+            compute_etag(b"", 5 * MB),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+    }
+
+    #[test]
+    fn test_compute_etag_multipart() {
+        let data = vec![b'a'; 10 * MB];
+        assert_eq!(
+            compute_etag(&data, 5 * MB),
+            "daebcb5e72f14b690c18018c9f92af05-2"
+        );
+
+        // The last part is shorter
+        let data = vec![b'a'; 11 * MB];
+        assert_eq!(
+            compute_etag(&data, 5 * MB),
+            "a24cb680bf8f661c7fd99c2fd4d3ac2d-3"
+        );
+    }
+}
@@ -6,8 +6,11 @@ pub use awsregion as region;
 
 pub use bucket::Bucket;
 pub use bucket::Tag;
-pub use bucket_ops::BucketConfiguration;
+pub use bucket_ops::{
+    BucketConfiguration, CopyConditions, GetObjectOptions, PresignPutOptions, PutObjectOptions,
+};
 pub use region::Region;
+pub use utils::{parse_s3_url, ParsedS3Location};
 
 pub mod bucket;
 pub mod bucket_ops;
@@ -18,11 +21,23 @@ pub mod signing;
 
 #[cfg(test)]
 pub mod bucket_tests;
+#[cfg(test)]
+mod test_utils;
 
+pub mod checksum;
+pub mod clock;
 pub mod error;
+pub mod etag;
+pub mod metrics;
+pub mod post_policy;
+pub mod provider;
 pub mod request;
+pub mod resolver;
+pub mod retry;
 pub mod utils;
 
 const LONG_DATETIME: &[time::format_description::FormatItem<'static>] =
     time::macros::format_description!("[year][month][day]T[hour][minute][second]Z");
 const EMPTY_PAYLOAD_SHA: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+const STREAMING_UNSIGNED_PAYLOAD_TRAILER: &str = "STREAMING-UNSIGNED-PAYLOAD-TRAILER";
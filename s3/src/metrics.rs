@@ -0,0 +1,68 @@
+//! Hooks to collect metrics on the requests a bucket sends
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use url::Url;
+
+/// Callbacks invoked around every HTTP request sent for a bucket, to feed Prometheus, statsd
+/// or the like. Every callback does nothing by default, implement the ones of interest.
+///
+/// `command` is the name of the [`Command`](crate::command::Command) sent, e.g. `GetObject`.
+/// Retried requests invoke [`on_request`](S3Metrics::on_request) and
+/// [`on_response`](S3Metrics::on_response) once per attempt. Requests that get no response,
+/// e.g. because they timed out, don't invoke [`on_response`](S3Metrics::on_response).
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use s3::metrics::S3Metrics;
+///
+/// #[derive(Default)]
+/// struct ErrorCount(AtomicU64);
+///
+/// impl S3Metrics for ErrorCount {
+///     fn on_response(&self, _command: &str, status: u16, _duration: Duration, _bytes: Option<u64>) {
+///         if status >= 500 {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let bucket_name = "rust-s3-test";
+/// let region = "us-east-1".parse().unwrap();
+/// let credentials = Credentials::default().unwrap();
+/// let errors = Arc::new(ErrorCount::default());
+///
+/// let bucket = Bucket::new(bucket_name, region, credentials)
+///     .unwrap()
+///     .with_metrics(errors.clone());
+/// ```
+pub trait S3Metrics: Send + Sync {
+    /// A request for `command` is about to be sent to `url`.
+    fn on_request(&self, _command: &str, _url: &Url) {}
+
+    /// The response headers to a request for `command` arrived with `status`, `duration`
+    /// after it was sent. `bytes` is the length of the response body, if known up front.
+    fn on_response(&self, _command: &str, _status: u16, _duration: Duration, _bytes: Option<u64>) {}
+
+    /// The request for `command` is about to be sent again, `attempt` being 1 for the first
+    /// retry.
+    fn on_retry(&self, _command: &str, _attempt: u32) {}
+}
+
+/// The metrics hooks of a bucket.
+#[derive(Clone)]
+pub(crate) struct Metrics(pub Arc<dyn S3Metrics>);
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
@@ -0,0 +1,289 @@
+//! Browser based uploads, with an HTML form POSTed straight to S3
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde_json::{json, Value};
+
+/// A form field a [`PostPolicy`] puts a condition on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PostPolicyField {
+    /// The key of the uploaded object
+    Key,
+    /// The `Content-Type` of the uploaded object
+    ContentType,
+    /// The canned ACL of the uploaded object
+    Acl,
+    /// The status S3 answers a successful upload with, `200`, `201` or `204` (the default)
+    /// when there's no `success_action_redirect`
+    SuccessActionStatus,
+    /// Where S3 redirects the browser to once the upload succeeded
+    SuccessActionRedirect,
+    /// Any other field, by name, e.g. `x-amz-meta-uuid`
+    Custom(String),
+}
+
+impl PostPolicyField {
+    /// Name of the form field.
+    pub fn name(&self) -> &str {
+        match self {
+            PostPolicyField::Key => "key",
+            PostPolicyField::ContentType => "Content-Type",
+            PostPolicyField::Acl => "acl",
+            PostPolicyField::SuccessActionStatus => "success_action_status",
+            PostPolicyField::SuccessActionRedirect => "success_action_redirect",
+            PostPolicyField::Custom(name) => name,
+        }
+    }
+}
+
+/// What a [`PostPolicyField`] may be set to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PostPolicyValue {
+    /// Exactly this value
+    Exact(String),
+    /// Any value starting with this prefix, any value at all if it's empty
+    StartsWith(String),
+}
+
+/// Policy of a browser based upload, the conditions the POSTed form has to meet for S3 to
+/// accept the upload. Sign it with [`Bucket::presign_post_policy`](crate::Bucket::presign_post_policy).
+///
+/// # Example
+///
+/// ```
+/// use s3::post_policy::{PostPolicy, PostPolicyField, PostPolicyValue};
+///
+/// let policy = PostPolicy::new(3600)
+///     .condition(PostPolicyField::Key, PostPolicyValue::StartsWith("uploads/".to_string()))
+///     .condition(PostPolicyField::ContentType, PostPolicyValue::Exact("image/png".to_string()))
+///     .condition(PostPolicyField::SuccessActionStatus, PostPolicyValue::Exact("201".to_string()))
+///     // Up to 10 MiB
+///     .content_length_range(1, 10 * 1024 * 1024);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PostPolicy {
+    expiration_secs: u32,
+    conditions: Vec<(PostPolicyField, PostPolicyValue)>,
+    content_length_range: Option<(u64, u64)>,
+}
+
+impl PostPolicy {
+    /// A policy valid for `expiration_secs` from the time it's signed, between 1 and 604800,
+    /// one week.
+    pub fn new(expiration_secs: u32) -> Self {
+        Self {
+            expiration_secs,
+            conditions: Vec::new(),
+            content_length_range: None,
+        }
+    }
+
+    /// Require `field` to meet `value`.
+    pub fn condition(mut self, field: PostPolicyField, value: PostPolicyValue) -> Self {
+        self.conditions.push((field, value));
+        self
+    }
+
+    /// Only accept uploaded files of `min` to `max` bytes, both included. The browser doesn't
+    /// know about the limit, S3 rejects files out of range once they're sent.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is larger than `max`.
+    pub fn content_length_range(mut self, min: u64, max: u64) -> Self {
+        assert!(min <= max, "content length range {}-{} is empty", min, max);
+        self.content_length_range = Some((min, max));
+        self
+    }
+
+    pub fn expiration_secs(&self) -> u32 {
+        self.expiration_secs
+    }
+
+    /// The conditions of the policy document, in its JSON form.
+    pub(crate) fn conditions_json(&self) -> Vec<Value> {
+        let mut conditions: Vec<Value> = self
+            .conditions
+            .iter()
+            .map(|(field, value)| {
+                let field = format!("${}", field.name());
+                match value {
+                    PostPolicyValue::Exact(value) => json!(["eq", field, value]),
+                    PostPolicyValue::StartsWith(prefix) => json!(["starts-with", field, prefix]),
+                }
+            })
+            .collect();
+        // Not a field, the bounds go in the array by themselves
+        if let Some((min, max)) = self.content_length_range {
+            conditions.push(json!(["content-length-range", min, max]));
+        }
+        conditions
+    }
+
+    /// The form fields meeting the conditions. A key only constrained by a prefix is the
+    /// prefix followed by `${filename}`, which S3 replaces with the name of the uploaded file.
+    pub(crate) fn fields(&self) -> BTreeMap<String, String> {
+        self.conditions
+            .iter()
+            .map(|(field, value)| {
+                let value = match (field, value) {
+                    (_, PostPolicyValue::Exact(value)) => value.clone(),
+                    (PostPolicyField::Key, PostPolicyValue::StartsWith(prefix)) => {
+                        format!("{}${{filename}}", prefix)
+                    }
+                    (_, PostPolicyValue::StartsWith(prefix)) => prefix.clone(),
+                };
+                (field.name().to_string(), value)
+            })
+            .collect()
+    }
+}
+
+/// A signed [`PostPolicy`]: where to POST the upload form to, and the fields it has to carry,
+/// the signature among them.
+#[derive(Clone, Debug)]
+pub struct PresignedPost {
+    pub url: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl PresignedPost {
+    /// Render a complete upload form, with every field as a hidden input, followed by the
+    /// file input and a submit button.
+    ///
+    /// With `redirect_to`, the browser is sent there once the upload succeeded, as
+    /// `success_action_redirect`. The policy needs a matching condition on that field for S3
+    /// to accept it.
+    pub fn to_html_form(&self, redirect_to: Option<&str>) -> String {
+        let mut fields = self.fields.clone();
+        if let Some(redirect_to) = redirect_to {
+            fields.insert(
+                "success_action_redirect".to_string(),
+                redirect_to.to_string(),
+            );
+        }
+
+        let mut form = format!(
+            "<form action=\"{}\" method=\"post\" enctype=\"multipart/form-data\">\n",
+            escape_html(&self.url)
+        );
+        for (name, value) in &fields {
+            // Writing to a String can't fail
+            let _ = writeln!(
+                form,
+                "  <input type=\"hidden\" name=\"{}\" value=\"{}\">",
+                escape_html(name),
+                escape_html(value)
+            );
+        }
+        // S3 ignores fields after the file
+        form.push_str("  <input type=\"file\" name=\"file\">\n");
+        form.push_str("  <input type=\"submit\" value=\"Upload\">\n");
+        form.push_str("</form>\n");
+        form
+    }
+}
+
+/// Escape `value` for use in HTML text or a quoted attribute.
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_conditions() {
+        let policy = PostPolicy::new(3600)
+            .condition(
+                PostPolicyField::Key,
+                PostPolicyValue::StartsWith("uploads/".to_string()),
+            )
+            .condition(
+                PostPolicyField::SuccessActionStatus,
+                PostPolicyValue::Exact("201".to_string()),
+            )
+            .condition(
+                PostPolicyField::SuccessActionRedirect,
+                PostPolicyValue::StartsWith("https://example.com/".to_string()),
+            )
+            .content_length_range(1, 10485760);
+
+        assert_eq!(
+            Value::Array(policy.conditions_json()),
+            json!([
+                ["starts-with", "$key", "uploads/"],
+                ["eq", "$success_action_status", "201"],
+                [
+                    "starts-with",
+                    "$success_action_redirect",
+                    "https://example.com/"
+                ],
+                ["content-length-range", 1, 10485760],
+            ])
+        );
+        assert_eq!(
+            policy.conditions_json()[3].to_string(),
+            r#"["content-length-range",1,10485760]"#
+        );
+
+        // The range isn't a form field
+        let fields = policy.fields();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields["success_action_status"], "201");
+        assert_eq!(fields["success_action_redirect"], "https://example.com/");
+    }
+
+    #[test]
+    #[should_panic(expected = "content length range 10-1 is empty")]
+    fn test_empty_content_length_range() {
+        PostPolicy::new(3600).content_length_range(10, 1);
+    }
+
+    #[test]
+    fn test_to_html_form() {
+        let mut fields = BTreeMap::new();
+        fields.insert("key".to_string(), "uploads/${filename}".to_string());
+        fields.insert("policy".to_string(), "eyJleHBpcmF0aW9uIjoi".to_string());
+        fields.insert(
+            "x-amz-meta-title".to_string(),
+            "\"Tom & Jerry\" <script>".to_string(),
+        );
+        let post = PresignedPost {
+            url: "https://rust-s3-test.s3.amazonaws.com".to_string(),
+            fields,
+        };
+
+        let form = post.to_html_form(Some("https://example.com/done?a=1&b=2"));
+        assert!(form.starts_with(
+            "<form action=\"https://rust-s3-test.s3.amazonaws.com\" method=\"post\" enctype=\"multipart/form-data\">\n"
+        ));
+        for input in [
+            "<input type=\"hidden\" name=\"key\" value=\"uploads/${filename}\">",
+            "<input type=\"hidden\" name=\"policy\" value=\"eyJleHBpcmF0aW9uIjoi\">",
+            "<input type=\"hidden\" name=\"x-amz-meta-title\" value=\"&quot;Tom &amp; Jerry&quot; &lt;script&gt;\">",
+            "<input type=\"hidden\" name=\"success_action_redirect\" value=\"https://example.com/done?a=1&amp;b=2\">",
+        ] {
+            assert!(form.contains(input), "{} missing from {}", input, form);
+        }
+        // The file comes after every other field
+        let file = form.find("<input type=\"file\" name=\"file\">").unwrap();
+        assert!(form.rfind("type=\"hidden\"").unwrap() < file);
+        assert!(form.ends_with("</form>\n"));
+
+        assert!(!post.to_html_form(None).contains("success_action_redirect"));
+    }
+}
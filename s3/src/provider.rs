@@ -0,0 +1,56 @@
+//! Presets for S3 compatible providers that need settings other than the defaults
+
+use awsregion::Region;
+
+/// An S3 compatible provider, to create a [`Bucket`](crate::Bucket) with the endpoint and
+/// settings it needs, see [`Bucket::for_provider`](crate::Bucket::for_provider).
+///
+/// | Provider | Endpoint                                 | Addressing | ListObjects |
+/// |----------|------------------------------------------|------------|-------------|
+/// | `Aws`    | the regional endpoint                    | subdomain  | v2          |
+/// | `Gcs`    | `storage.googleapis.com`                 | subdomain  | v1          |
+/// | `R2`     | `<account_id>.r2.cloudflarestorage.com`  | subdomain  | v2          |
+/// | `Minio`  | `endpoint`                               | path       | v2          |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Provider {
+    Aws(Region),
+    /// Google Cloud Storage through its XML API, with HMAC keys as credentials
+    Gcs,
+    /// Cloudflare R2
+    R2 {
+        account_id: String,
+    },
+    /// MinIO, or anything else served from a single host, e.g. `http://localhost:9000`
+    Minio {
+        endpoint: String,
+    },
+}
+
+impl Provider {
+    pub fn region(&self) -> Region {
+        match self {
+            Provider::Aws(region) => region.clone(),
+            Provider::Gcs => Region::Custom {
+                region: "auto".to_string(),
+                endpoint: "https://storage.googleapis.com".to_string(),
+            },
+            Provider::R2 { account_id } => Region::R2 {
+                account_id: account_id.clone(),
+            },
+            Provider::Minio { endpoint } => Region::Custom {
+                region: "us-east-1".to_string(),
+                endpoint: endpoint.clone(),
+            },
+        }
+    }
+
+    /// Whether buckets are addressed as part of the path instead of the hostname.
+    pub fn path_style(&self) -> bool {
+        matches!(self, Provider::Minio { .. })
+    }
+
+    /// Whether the provider supports ListObjectsV2, v1 is used otherwise.
+    pub fn listobjects_v2(&self) -> bool {
+        !matches!(self, Provider::Gcs)
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use flate2::write::{GzDecoder, ZlibDecoder};
+
+/// Incrementally inflates a response body sent with a `gzip` or `deflate` `Content-Encoding`.
+pub(crate) enum ContentDecoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+}
+
+impl ContentDecoder {
+    /// Decoder for the `Content-Encoding` header value, `None` for encodings other than
+    /// `gzip` and `deflate`.
+    pub fn new(content_encoding: &str) -> Option<Self> {
+        match content_encoding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentDecoder::Gzip(GzDecoder::new(Vec::new()))),
+            "deflate" => Some(ContentDecoder::Deflate(ZlibDecoder::new(Vec::new()))),
+            _ => None,
+        }
+    }
+
+    /// Feed the next `chunk` of the encoded body, returning what could be inflated so far.
+    pub fn decode(&mut self, chunk: &[u8]) -> std::io::Result<Vec<u8>> {
+        let decoded = match self {
+            ContentDecoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.get_mut()
+            }
+            ContentDecoder::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.get_mut()
+            }
+        };
+        Ok(std::mem::take(decoded))
+    }
+
+    /// Inflate what is left once the whole body has been fed.
+    pub fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            ContentDecoder::Gzip(decoder) => decoder.finish(),
+            ContentDecoder::Deflate(decoder) => decoder.finish(),
+        }
+    }
+}
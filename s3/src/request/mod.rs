@@ -1,7 +1,10 @@
 mod tokio_backend;
 pub use tokio_backend::*;
 
+mod decompress;
+pub(crate) use decompress::ContentDecoder;
+
 mod request_trait;
 pub use request_trait::*;
 
-pub(crate) use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+pub(crate) use tokio::io::AsyncReadExt;
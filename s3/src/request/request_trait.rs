@@ -6,17 +6,24 @@ use time::format_description::well_known::Rfc2822;
 use time::OffsetDateTime;
 use url::Url;
 
-use crate::bucket::Bucket;
+use crate::bucket::{validate_expiry_within_credentials, Bucket};
+use crate::bucket_ops::AclConfiguration;
+use crate::checksum::ChecksumAlgorithm;
 use crate::command::Command;
 use crate::error::S3Error;
 use crate::signing;
-use crate::LONG_DATETIME;
+use crate::utils::object_key;
+use crate::{LONG_DATETIME, UNSIGNED_PAYLOAD};
 use bytes::Bytes;
+use futures::StreamExt;
 use http::header::{
-    HeaderName, ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST, RANGE,
+    HeaderName, ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST,
+    IF_MATCH, IF_MODIFIED_SINCE, RANGE,
 };
 use http::HeaderMap;
+use serde::de::DeserializeOwned;
 use std::fmt::Write as _;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 
 #[derive(Debug)]
 
@@ -57,10 +64,17 @@ impl ResponseData {
         self.status_code
     }
 
+    /// The body as text, failing on invalid UTF-8. Use this when the body is going to be parsed.
     pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
         std::str::from_utf8(self.as_slice())
     }
 
+    /// The body as text, with invalid UTF-8 replaced by `U+FFFD`. Good enough for diagnostics
+    /// like error messages, not for parsing.
+    pub fn as_str_lossy(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(self.as_slice())
+    }
+
     pub fn to_string(&self) -> Result<String, std::str::Utf8Error> {
         std::str::from_utf8(self.as_slice()).map(|s| s.to_string())
     }
@@ -68,6 +82,50 @@ impl ResponseData {
     pub fn headers(&self) -> HashMap<String, String> {
         self.headers.clone()
     }
+
+    /// Value of the response header `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// The `x-amz-request-id` of the response, useful when contacting support.
+    pub fn request_id(&self) -> Option<&str> {
+        self.header("x-amz-request-id")
+    }
+
+    /// The `x-amz-id-2` (extended request id) of the response.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.header("x-amz-id-2")
+    }
+
+    /// Checksum of the object S3 stored along with it, as returned when the request sent
+    /// `x-amz-checksum-mode: ENABLED`, see [`GetObjectOptions::checksum_mode`](crate::GetObjectOptions::checksum_mode).
+    /// Checksums of objects uploaded in parts end in `-<parts count>`.
+    pub fn checksum(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        self.header(algorithm.header_name())
+    }
+
+    /// Whether the body matches the checksum S3 stored for the object, `None` if it returned
+    /// none to check against, or only the checksum of the parts of an object uploaded in parts.
+    pub fn verify_checksum(&self, algorithm: ChecksumAlgorithm) -> Option<bool> {
+        let expected = self.checksum(algorithm)?;
+        if expected.contains('-') {
+            return None;
+        }
+        Some(algorithm.compute(self.as_slice()) == expected)
+    }
+
+    /// Deserialize the body as XML, the format of S3 API responses.
+    pub fn deserialize_xml<T: DeserializeOwned>(&self) -> Result<T, S3Error> {
+        Ok(quick_xml::de::from_str(self.as_str()?)?)
+    }
+
+    /// Deserialize the body as JSON, e.g. an object holding a JSON document.
+    pub fn deserialize_json<T: DeserializeOwned>(&self) -> Result<T, S3Error> {
+        Ok(serde_json::from_slice(self.as_slice())?)
+    }
 }
 
 use std::fmt;
@@ -78,8 +136,7 @@ impl fmt::Display for ResponseData {
             f,
             "Status code: {}\n Data: {}",
             self.status_code(),
-            self.to_string()
-                .unwrap_or_else(|_| "Data could not be cast to UTF string".to_string())
+            self.as_str_lossy()
         )
     }
 }
@@ -92,12 +149,141 @@ pub type StreamItem = Result<bytes::Bytes, crate::error::S3Error>;
 pub struct ResponseDataStream {
     pub bytes: DataStream,
     pub status_code: u16,
+    /// Response headers, keyed by their lowercase names
+    pub headers: HashMap<String, String>,
 }
 
 impl ResponseDataStream {
     pub fn bytes(&mut self) -> &mut DataStream {
         &mut self.bytes
     }
+
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    pub fn headers(&self) -> HashMap<String, String> {
+        self.headers.clone()
+    }
+
+    /// Value of the response header `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// The `x-amz-request-id` of the response, useful when contacting support.
+    pub fn request_id(&self) -> Option<&str> {
+        self.header("x-amz-request-id")
+    }
+
+    /// The `x-amz-id-2` (extended request id) of the response.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.header("x-amz-id-2")
+    }
+
+    /// Read the rest of the body into memory, failing on the first chunk that failed.
+    pub async fn collect(mut self) -> Result<Bytes, S3Error> {
+        let mut body = bytes::BytesMut::new();
+        while let Some(chunk) = self.bytes.next().await {
+            body.extend_from_slice(&chunk?);
+        }
+        Ok(body.freeze())
+    }
+
+    /// Write the rest of the body to `writer`, returning the number of bytes written. Fails on
+    /// the first chunk that failed, after writing the chunks before it.
+    pub async fn write_to<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<u64, S3Error> {
+        let mut written = 0;
+        while let Some(chunk) = self.bytes.next().await {
+            let chunk = chunk?;
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+
+    /// The rest of the body split into UTF-8 lines, without their `\n` or `\r\n` ending, e.g.
+    /// for CSV or JSON Lines. Lines split across chunks are put back together, and the last
+    /// line is yielded whether or not it ends with a newline. A line that isn't valid UTF-8
+    /// fails with [`S3Error::FromUtf8`], a chunk that failed ends the stream with its error.
+    pub fn into_lines(self) -> impl futures::Stream<Item = Result<String, S3Error>> + Send {
+        let lines = Lines {
+            bytes: self.bytes,
+            pending: Vec::new(),
+            scanned: 0,
+            done: false,
+        };
+        futures::stream::unfold(lines, |mut lines| async move {
+            let line = lines.next_line().await?;
+            Some((line, lines))
+        })
+    }
+
+    /// The rest of the body as an [`AsyncRead`], e.g. for [`tokio::io::copy`] or a parser.
+    /// A chunk that failed fails the read with an [`std::io::Error`], wrapping the
+    /// [`S3Error`] unless it already was an IO error.
+    pub fn into_reader(self) -> impl AsyncRead + Send + Unpin {
+        tokio_util::io::StreamReader::new(self.bytes.map(|chunk| {
+            chunk.map_err(|error| match error {
+                S3Error::Io(error) => error,
+                error => std::io::Error::other(error),
+            })
+        }))
+    }
+}
+
+/// State of [`ResponseDataStream::into_lines`], the bytes received past the last line.
+struct Lines {
+    bytes: DataStream,
+    pending: Vec<u8>,
+    /// How much of `pending` is known not to hold a newline
+    scanned: usize,
+    done: bool,
+}
+
+impl Lines {
+    async fn next_line(&mut self) -> Option<Result<String, S3Error>> {
+        loop {
+            if let Some(newline) = self.pending[self.scanned..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+            {
+                let mut line: Vec<u8> = self.pending.drain(..=self.scanned + newline).collect();
+                self.scanned = 0;
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return Some(String::from_utf8(line).map_err(S3Error::from));
+            }
+            self.scanned = self.pending.len();
+            if self.done {
+                if self.pending.is_empty() {
+                    return None;
+                }
+                // The last line, without a newline
+                self.scanned = 0;
+                let line = std::mem::take(&mut self.pending);
+                return Some(String::from_utf8(line).map_err(S3Error::from));
+            }
+            match self.bytes.next().await {
+                Some(Ok(chunk)) => self.pending.extend_from_slice(&chunk),
+                Some(Err(error)) => {
+                    self.done = true;
+                    self.pending.clear();
+                    self.scanned = 0;
+                    return Some(Err(error));
+                }
+                None => self.done = true,
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -115,7 +301,7 @@ pub trait Request {
     async fn response_header(&self) -> Result<(Self::HeaderMap, u16), S3Error>;
     fn datetime(&self) -> OffsetDateTime;
     fn bucket(&self) -> Bucket;
-    fn command(&self) -> Command;
+    fn command(&self) -> Command<'_>;
     fn path(&self) -> String;
 
     fn signing_key(&self) -> Result<Vec<u8>, S3Error> {
@@ -123,14 +309,18 @@ pub trait Request {
             &self.datetime(),
             &self
                 .bucket()
-                .secret_key()?
-                .expect("Secret key must be provided to sign headers, found None"),
+                .signing_keys()?
+                .ok_or(S3Error::MissingCredentials)?
+                .1,
             &self.bucket().region(),
             "s3",
         )
     }
 
     fn request_body(&self) -> Vec<u8> {
+        if let Some(payload) = self.command().configuration_payload() {
+            return payload.into_bytes();
+        }
         match self.command() {
             Command::PutObject { content, .. } => Vec::from(content),
             Command::PutObjectTagging { tags } => Vec::from(tags),
@@ -160,11 +350,14 @@ pub trait Request {
     }
 
     fn host_header(&self) -> String {
-        self.bucket().host()
+        self.bucket().host_header()
     }
 
-    fn presigned(&self) -> Result<String, S3Error> {
-        let (expiry, custom_headers, custom_queries) = match self.command() {
+    #[allow(clippy::type_complexity)]
+    fn presign_params(
+        &self,
+    ) -> Result<(u32, Option<HeaderMap>, Option<HashMap<String, String>>), S3Error> {
+        Ok(match self.command() {
             Command::PresignGet {
                 expiry_secs,
                 custom_queries,
@@ -174,8 +367,31 @@ pub trait Request {
                 custom_headers,
             } => (expiry_secs, custom_headers, None),
             Command::PresignDelete { expiry_secs } => (expiry_secs, None, None),
+            Command::PresignInitiateMultipartUpload {
+                expiry_secs,
+                content_type,
+            } => {
+                let mut custom_headers = HeaderMap::new();
+                custom_headers.insert(CONTENT_TYPE, content_type.parse()?);
+                (expiry_secs, Some(custom_headers), None)
+            }
+            Command::PresignCompleteMultipartUpload { expiry_secs, .. } => {
+                (expiry_secs, None, None)
+            }
+            Command::Presign {
+                expiry_secs,
+                custom_headers,
+                custom_queries,
+                ..
+            } => (expiry_secs, custom_headers, custom_queries),
             _ => unreachable!(),
-        };
+        })
+    }
+
+    fn presigned(&self) -> Result<String, S3Error> {
+        let (expiry, custom_headers, custom_queries) = self.presign_params()?;
+
+        validate_expiry_within_credentials(&self.bucket(), expiry, self.datetime())?;
 
         Ok(format!(
             "{}&X-Amz-Signature={}",
@@ -206,24 +422,13 @@ pub trait Request {
     }
 
     fn presigned_canonical_request(&self, headers: &HeaderMap) -> Result<String, S3Error> {
-        let (expiry, custom_headers, custom_queries) = match self.command() {
-            Command::PresignGet {
-                expiry_secs,
-                custom_queries,
-            } => (expiry_secs, None, custom_queries),
-            Command::PresignPut {
-                expiry_secs,
-                custom_headers,
-            } => (expiry_secs, custom_headers, None),
-            Command::PresignDelete { expiry_secs } => (expiry_secs, None, None),
-            _ => unreachable!(),
-        };
+        let (expiry, custom_headers, custom_queries) = self.presign_params()?;
 
         signing::canonical_request(
             &self.command().http_verb().to_string(),
             &self.presigned_url_no_sig(expiry, custom_headers.as_ref(), custom_queries.as_ref())?,
             headers,
-            "UNSIGNED-PAYLOAD",
+            UNSIGNED_PAYLOAD,
         )
     }
 
@@ -239,17 +444,23 @@ pub trait Request {
         } else {
             bucket.session_token()?
         };
+        let mut query_params = signing::authorization_query_params_no_sig(
+            &bucket.signing_keys()?.ok_or(S3Error::MissingCredentials)?.0,
+            &self.datetime(),
+            &self.bucket().region(),
+            expiry,
+            custom_headers,
+            token.as_ref(),
+        )?;
+        let url = self.url()?;
+        // Commands like `?uploads` already open the query string
+        if url.query().is_some() {
+            query_params.replace_range(..1, "&");
+        }
         let url = Url::parse(&format!(
             "{}{}{}",
-            self.url()?,
-            &signing::authorization_query_params_no_sig(
-                &self.bucket().access_key()?.unwrap_or_default(),
-                &self.datetime(),
-                &self.bucket().region(),
-                expiry,
-                custom_headers,
-                token.as_ref()
-            )?,
+            url,
+            query_params,
             &signing::flatten_queries(custom_queries)?,
         ))?;
 
@@ -267,28 +478,50 @@ pub trait Request {
             return Ok(Url::parse(&url_str)?);
         }
 
-        let path = if self.path().starts_with('/') {
-            self.path()[1..].to_string()
-        } else {
-            self.path()[..].to_string()
-        };
-
+        let path = self.path();
         url_str.push('/');
-        url_str.push_str(&signing::uri_encode(&path, false));
+        url_str.push_str(&signing::uri_encode(object_key(&path), false));
 
         // Append to url_path
         #[allow(clippy::collapsible_match)]
         match self.command() {
-            Command::InitiateMultipartUpload { .. } | Command::ListMultipartUploads { .. } => {
-                url_str.push_str("?uploads")
-            }
+            Command::InitiateMultipartUpload { .. }
+            | Command::ListMultipartUploads { .. }
+            | Command::PresignInitiateMultipartUpload { .. } => url_str.push_str("?uploads"),
             Command::AbortMultipartUpload { upload_id } => {
                 write!(url_str, "?uploadId={}", upload_id).expect("Could not write to url_str");
             }
-            Command::CompleteMultipartUpload { upload_id, .. } => {
+            Command::CompleteMultipartUpload { upload_id, .. }
+            | Command::PresignCompleteMultipartUpload { upload_id, .. } => {
                 write!(url_str, "?uploadId={}", upload_id).expect("Could not write to url_str");
             }
             Command::GetObjectTorrent => url_str.push_str("?torrent"),
+            Command::GetObjectAttributes { .. } => url_str.push_str("?attributes"),
+            Command::GetBucketLocation => url_str.push_str("?location"),
+            Command::PutBucketCors { .. } => url_str.push_str("?cors"),
+            Command::PutBucketEncryption { .. }
+            | Command::GetBucketEncryption
+            | Command::DeleteBucketEncryption => url_str.push_str("?encryption"),
+            Command::PutBucketAccelerateConfiguration { .. }
+            | Command::GetBucketAccelerateConfiguration => url_str.push_str("?accelerate"),
+            Command::PutBucketAcl { .. } | Command::GetBucketAcl | Command::PutObjectAcl { .. } => {
+                url_str.push_str("?acl")
+            }
+            Command::PutBucketOwnershipControls { .. } | Command::GetBucketOwnershipControls => {
+                url_str.push_str("?ownershipControls")
+            }
+            Command::PutBucketNotificationConfiguration { .. }
+            | Command::GetBucketNotificationConfiguration => url_str.push_str("?notification"),
+            Command::DeleteObjects { .. } => url_str.push_str("?delete"),
+            Command::PutBucketConfiguration { kind, .. }
+            | Command::GetBucketConfiguration { kind, .. }
+            | Command::DeleteBucketConfiguration { kind, .. }
+            | Command::ListBucketConfigurations { kind, .. } => {
+                write!(url_str, "?{}", kind.subresource()).expect("Could not write to url_str");
+            }
+            Command::GetObjectPart { part_number } => {
+                write!(url_str, "?partNumber={}", part_number).expect("Could not write to url_str");
+            }
             Command::PutObject { multipart, .. } => {
                 if let Some(multipart) = multipart {
                     url_str.push_str(&multipart.query_string())
@@ -303,6 +536,31 @@ pub trait Request {
             url.query_pairs_mut().append_pair(key, value);
         }
 
+        if let Command::HeadObjectVersion { version_id } = self.command() {
+            url.query_pairs_mut().append_pair("versionId", version_id);
+        }
+        if let Command::HeadObjectWithQuery { queries } = self.command() {
+            for (key, value) in queries {
+                url.query_pairs_mut().append_pair(key, value);
+            }
+        }
+
+        match self.command() {
+            Command::PutBucketConfiguration { id, .. }
+            | Command::GetBucketConfiguration { id, .. }
+            | Command::DeleteBucketConfiguration { id, .. } => {
+                url.query_pairs_mut().append_pair("id", id);
+            }
+            Command::ListBucketConfigurations {
+                continuation_token: Some(continuation_token),
+                ..
+            } => {
+                url.query_pairs_mut()
+                    .append_pair("continuation-token", continuation_token);
+            }
+            _ => {}
+        }
+
         if let Command::ListObjectsV2 {
             prefix,
             delimiter,
@@ -351,6 +609,7 @@ pub trait Request {
                 prefix,
                 delimiter,
                 key_marker,
+                upload_id_marker,
                 max_uploads,
             } => {
                 let mut query_pairs = url.query_pairs_mut();
@@ -361,6 +620,9 @@ pub trait Request {
                 if let Some(key_marker) = key_marker {
                     query_pairs.append_pair("key-marker", &key_marker);
                 }
+                if let Some(upload_id_marker) = upload_id_marker {
+                    query_pairs.append_pair("upload-id-marker", &upload_id_marker);
+                }
                 if let Some(max_uploads) = max_uploads {
                     query_pairs.append_pair("max-uploads", max_uploads.to_string().as_str());
                 }
@@ -370,18 +632,35 @@ pub trait Request {
             | Command::DeleteObjectTagging => {
                 url.query_pairs_mut().append_pair("tagging", "");
             }
+            Command::GetObjectAs {
+                response_content_type,
+            } => {
+                url.query_pairs_mut()
+                    .append_pair("response-content-type", response_content_type);
+            }
             _ => {}
         }
 
         Ok(url)
     }
 
+    /// The `x-amz-content-sha256` of the request, `UNSIGNED-PAYLOAD` for the content of
+    /// uploads to a bucket set to leave it unsigned.
+    fn payload_sha256(&self) -> String {
+        match self.command() {
+            Command::PutObject { sha256: None, .. } if self.bucket().is_unsigned_payload() => {
+                UNSIGNED_PAYLOAD.into()
+            }
+            command => command.sha256(),
+        }
+    }
+
     fn canonical_request(&self, headers: &HeaderMap) -> Result<String, S3Error> {
         signing::canonical_request(
             &self.command().http_verb().to_string(),
             &self.url()?,
             headers,
-            &self.command().sha256(),
+            &self.payload_sha256(),
         )
     }
 
@@ -393,7 +672,11 @@ pub trait Request {
         let signature = hex::encode(hmac.finalize().into_bytes());
         let signed_header = signing::signed_header_string(headers);
         signing::authorization_header(
-            &self.bucket().access_key()?.expect("No access_key provided"),
+            &self
+                .bucket()
+                .signing_keys()?
+                .ok_or(S3Error::MissingCredentials)?
+                .0,
             &self.datetime(),
             &self.bucket().region(),
             &signed_header,
@@ -403,7 +686,7 @@ pub trait Request {
 
     fn headers(&self) -> Result<HeaderMap, S3Error> {
         // Generate this once, but it's used in more than one place.
-        let sha256 = self.command().sha256();
+        let sha256 = self.payload_sha256();
 
         // Start with extra_headers, that way our headers replace anything with
         // the same name.
@@ -419,14 +702,85 @@ pub trait Request {
         headers.insert(HOST, host_header.parse()?);
 
         match self.command() {
-            Command::CopyObject { from } => {
+            Command::CopyObject { from, conditions } => {
                 headers.insert(HeaderName::from_static("x-amz-copy-source"), from.parse()?);
+                // The copy keeps the metadata of the source, which is also the default
+                headers.insert(
+                    HeaderName::from_static("x-amz-metadata-directive"),
+                    "COPY".parse()?,
+                );
+                if let Some(conditions) = conditions {
+                    conditions.add_headers(&mut headers)?;
+                }
             }
             Command::ListObjects { .. } => {}
             Command::ListObjectsV2 { .. } => {}
             Command::GetObject => {}
+            Command::GetObjectAs { .. } => {}
+            Command::GetObjectWithOptions { options } => {
+                options.add_headers(&mut headers)?;
+            }
+            Command::GetObjectPart { .. } => {}
             Command::GetObjectTagging => {}
+            Command::GetObjectAttributes {
+                attributes,
+                part_number_marker,
+            } => {
+                let attributes = attributes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                headers.insert(
+                    HeaderName::from_static("x-amz-object-attributes"),
+                    attributes.parse()?,
+                );
+                if let Some(part_number_marker) = part_number_marker {
+                    headers.insert(
+                        HeaderName::from_static("x-amz-part-number-marker"),
+                        part_number_marker.to_string().parse()?,
+                    );
+                }
+            }
             Command::GetBucketLocation => {}
+            Command::GetBucketEncryption => {}
+            Command::GetBucketAccelerateConfiguration => {}
+            Command::GetBucketAcl => {}
+            Command::GetBucketOwnershipControls => {}
+            Command::GetBucketNotificationConfiguration => {}
+            Command::GetBucketConfiguration { .. } => {}
+            Command::ListBucketConfigurations { .. } => {}
+            // Without a length up front, the body goes out with chunked transfer encoding
+            Command::PutObjectReader {
+                content_length,
+                checksum: None,
+                ..
+            } => {
+                if let Some(content_length) = content_length {
+                    headers.insert(CONTENT_LENGTH, content_length.to_string().parse()?);
+                }
+                headers.insert(CONTENT_TYPE, self.command().content_type().parse()?);
+            }
+            // The payload is framed as aws-chunked, ending with a trailer, which makes the
+            // length on the wire differ from the object's
+            Command::PutObjectReader {
+                content_length,
+                checksum: Some(checksum),
+                ..
+            } => {
+                if let Some(content_length) = content_length {
+                    headers.insert(
+                        HeaderName::from_static("x-amz-decoded-content-length"),
+                        content_length.to_string().parse()?,
+                    );
+                }
+                headers.insert(CONTENT_ENCODING, "aws-chunked".parse()?);
+                headers.insert(
+                    HeaderName::from_static("x-amz-trailer"),
+                    checksum.header_name().parse()?,
+                );
+                headers.insert(CONTENT_TYPE, self.command().content_type().parse()?);
+            }
             _ => {
                 headers.insert(
                     CONTENT_LENGTH,
@@ -456,6 +810,27 @@ pub trait Request {
             );
         }
 
+        if let Command::PutBucketAcl {
+            acl: AclConfiguration::Canned(acl),
+        }
+        | Command::PutObjectAcl {
+            acl: AclConfiguration::Canned(acl),
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-acl"),
+                acl.to_string().parse()?,
+            );
+        }
+
+        if let Command::PutObject {
+            options: Some(options),
+            ..
+        } = self.command()
+        {
+            options.add_headers(&mut headers)?;
+        }
+
         if let Command::PutObjectTagging { tags } = self.command() {
             let digest = md5::compute(tags);
             let hash = general_purpose::STANDARD.encode(digest.as_ref());
@@ -464,11 +839,19 @@ pub trait Request {
             let digest = md5::compute(content);
             let hash = general_purpose::STANDARD.encode(digest.as_ref());
             headers.insert(HeaderName::from_static("content-md5"), hash.parse()?);
+        } else if let Some(payload) = self.command().configuration_payload() {
+            let digest = md5::compute(payload);
+            let hash = general_purpose::STANDARD.encode(digest.as_ref());
+            headers.insert(HeaderName::from_static("content-md5"), hash.parse()?);
         } else if let Command::UploadPart { content, .. } = self.command() {
             let digest = md5::compute(content);
             let hash = general_purpose::STANDARD.encode(digest.as_ref());
             headers.insert(HeaderName::from_static("content-md5"), hash.parse()?);
-        } else if let Command::GetObject {} = self.command() {
+        } else if let Command::GetObject {}
+        | Command::GetObjectAs { .. }
+        | Command::GetObjectWithOptions { .. }
+        | Command::GetObjectPart { .. } = self.command()
+        {
             headers.insert(ACCEPT, "application/octet-stream".to_string().parse()?);
         // headers.insert(header::ACCEPT_CHARSET, HeaderValue::from_str("UTF-8")?);
         } else if let Command::GetObjectRange { start, end } = self.command() {
@@ -481,12 +864,26 @@ pub trait Request {
             }
 
             headers.insert(RANGE, range.parse()?);
+        } else if let Command::GetObjectRanges { ranges } = self.command() {
+            let ranges = ranges
+                .iter()
+                .map(|(start, end)| match end {
+                    Some(end) => format!("{}-{}", start, end),
+                    None => format!("{}-", start),
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            headers.insert(RANGE, format!("bytes={}", ranges).parse()?);
         } else if let Command::CreateBucket { ref config } = self.command() {
             config.add_headers(&mut headers)?;
+        } else if let Command::HeadObjectIfModifiedSince { since } = self.command() {
+            headers.insert(IF_MODIFIED_SINCE, since.format(&Rfc2822)?.parse()?);
+        } else if let Command::DeleteObjectIfMatch { etag } = self.command() {
+            headers.insert(IF_MATCH, etag.parse()?);
         }
 
-        // This must be last, as it signs the other headers, omitted if no secret key is provided
-        if self.bucket().secret_key()?.is_some() {
+        // This must be last, as it signs the other headers, omitted for anonymous credentials
+        if self.bucket().signing_keys()?.is_some() {
             let authorization = self.authorization(&headers)?;
             headers.insert(AUTHORIZATION, authorization.parse()?);
         }
@@ -502,3 +899,163 @@ pub trait Request {
         Ok(headers)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ResponseData, ResponseDataStream};
+    use crate::error::S3Error;
+    use crate::serde_types::ListBucketResult;
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use std::collections::HashMap;
+
+    fn response_data_stream(chunks: Vec<Result<&'static str, S3Error>>) -> ResponseDataStream {
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| chunk.map(|chunk| Bytes::from_static(chunk.as_bytes())));
+        ResponseDataStream {
+            bytes: Box::pin(futures::stream::iter(chunks)),
+            status_code: 200,
+            headers: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_data_stream_collect() {
+        let stream = response_data_stream(vec![Ok("first "), Ok("second "), Ok("third")]);
+        assert_eq!(stream.collect().await.unwrap(), "first second third");
+
+        let stream = response_data_stream(vec![Ok("first "), Err(S3Error::HttpFail), Ok("third")]);
+        assert!(matches!(stream.collect().await, Err(S3Error::HttpFail)));
+    }
+
+    #[tokio::test]
+    async fn test_response_data_stream_write_to() {
+        let mut stream = response_data_stream(vec![Ok("first "), Ok("second "), Ok("third")]);
+        let mut written = Vec::new();
+        assert_eq!(stream.write_to(&mut written).await.unwrap(), 18);
+        assert_eq!(written, b"first second third");
+
+        let mut stream =
+            response_data_stream(vec![Ok("first "), Err(S3Error::HttpFail), Ok("third")]);
+        let mut written = Vec::new();
+        assert!(matches!(
+            stream.write_to(&mut written).await,
+            Err(S3Error::HttpFail)
+        ));
+        assert_eq!(written, b"first ");
+    }
+
+    #[tokio::test]
+    async fn test_response_data_stream_into_reader() {
+        let stream = response_data_stream(vec![Ok("first "), Ok("second "), Ok("third")]);
+        let mut read = Vec::new();
+        tokio::io::copy(&mut stream.into_reader(), &mut read)
+            .await
+            .unwrap();
+        assert_eq!(read, b"first second third");
+
+        let stream = response_data_stream(vec![Ok("first "), Err(S3Error::HttpFail), Ok("third")]);
+        let mut read = Vec::new();
+        let error = tokio::io::copy(&mut stream.into_reader(), &mut read)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+        assert!(matches!(
+            error.into_inner().unwrap().downcast_ref::<S3Error>(),
+            Some(S3Error::HttpFail)
+        ));
+        assert_eq!(read, b"first ");
+    }
+
+    #[tokio::test]
+    async fn test_response_data_stream_into_lines() {
+        let lines = |chunks| async move {
+            response_data_stream(chunks)
+                .into_lines()
+                .collect::<Vec<_>>()
+                .await
+        };
+
+        let read = lines(vec![
+            Ok("a,b\nfirst,li"),
+            Ok("ne\r\nsecond"),
+            Ok(",line\nlast"),
+        ])
+        .await;
+        assert_eq!(
+            read.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            ["a,b", "first,line", "second,line", "last"]
+        );
+
+        // A trailing newline doesn't make for an empty last line, empty lines are kept
+        let read = lines(vec![Ok("\nfirst\n"), Ok("\n")]).await;
+        assert_eq!(
+            read.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            ["", "first", ""]
+        );
+
+        let read = lines(vec![Ok("first\nsec"), Err(S3Error::HttpFail), Ok("ond\n")]).await;
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].as_deref().unwrap(), "first");
+        assert!(matches!(read[1], Err(S3Error::HttpFail)));
+    }
+
+    fn response_data(body: &'static str) -> ResponseData {
+        ResponseData::new(Bytes::from_static(body.as_bytes()), 200, HashMap::new())
+    }
+
+    #[test]
+    fn test_as_str_strict_and_lossy() {
+        let data = ResponseData::new(
+            Bytes::from_static(b"Access denied \xff"),
+            403,
+            HashMap::new(),
+        );
+        assert!(data.as_str().is_err());
+        assert_eq!(data.as_str_lossy(), "Access denied \u{FFFD}");
+    }
+
+    #[test]
+    fn test_deserialize_xml() {
+        let data = response_data(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Name>rust-s3-test</Name>
+                <Prefix></Prefix>
+                <IsTruncated>false</IsTruncated>
+                <Contents>
+                    <Key>test.file</Key>
+                    <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                    <Size>4</Size>
+                </Contents>
+            </ListBucketResult>"#,
+        );
+        let result: ListBucketResult = data.deserialize_xml().unwrap();
+        assert_eq!(result.name, "rust-s3-test");
+        assert_eq!(result.contents[0].key, "test.file");
+
+        assert!(response_data("not xml")
+            .deserialize_xml::<ListBucketResult>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_deserialize_json() {
+        #[derive(serde::Deserialize)]
+        struct Manifest {
+            version: u32,
+            files: Vec<String>,
+        }
+
+        let data = response_data(r#"{"version": 2, "files": ["a.file", "b.file"]}"#);
+        let manifest: Manifest = data.deserialize_json().unwrap();
+        assert_eq!(manifest.version, 2);
+        assert_eq!(manifest.files, vec!["a.file", "b.file"]);
+
+        assert!(matches!(
+            response_data("{").deserialize_json::<Manifest>(),
+            Err(crate::error::S3Error::SerdeJson(_))
+        ));
+    }
+}
@@ -3,28 +3,98 @@ extern crate md5;
 
 use bytes::Bytes;
 use futures::TryStreamExt;
+use hyper::body::HttpBody;
+use hyper::client::connect::dns::{GaiResolver, Name};
+use hyper::client::HttpConnector;
+use hyper::service::Service;
 use hyper::{Body, Client};
-use hyper_tls::HttpsConnector;
 use std::collections::HashMap;
+use std::future::Future;
+use std::io::SeekFrom;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use time::OffsetDateTime;
 
 use super::request_trait::{Request, ResponseData};
+use super::ContentDecoder;
 use crate::bucket::Bucket;
+use crate::checksum::ChecksumAlgorithm;
 use crate::command::Command;
 use crate::command::HttpMethod;
 use crate::error::S3Error;
+use crate::resolver::Resolver;
+use crate::retry::{parse_retry_after, RetryPolicy};
+use crate::utils::{error_from_response_data, parse_content_range};
+use http::header::{CONTENT_ENCODING, CONTENT_RANGE, DATE, RETRY_AFTER};
+use time::format_description::well_known::Rfc2822;
 
 use tokio_stream::StreamExt;
 
 pub use crate::request::tokio_backend::HyperRequest as RequestImpl;
 pub use tokio::io::AsyncRead;
+use tokio::io::AsyncSeekExt;
 pub use tokio::io::{AsyncWrite, AsyncWriteExt};
 pub use tokio_stream::Stream;
 
-use tracing::{event, span, Level};
+use tracing::{event, span, Instrument, Level};
 
 use crate::request::request_trait::ResponseDataStream;
 
+/// Hidden file next to `path` to download into before replacing `path`, unique within the
+/// process so that concurrent downloads to the same path don't write into each other.
+fn partial_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.part",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// When the bucket's request timeout runs out for the last attempt of a request, kept in the
+/// extensions of its response so that buffering the body is bounded too.
+#[derive(Clone, Copy)]
+struct Deadline {
+    at: tokio::time::Instant,
+    timeout: Duration,
+}
+
+impl Deadline {
+    fn after(timeout: Option<Duration>) -> Option<Self> {
+        timeout.map(|timeout| Deadline {
+            at: tokio::time::Instant::now() + timeout,
+            timeout,
+        })
+    }
+
+    fn of(response: &http::Response<Body>) -> Option<Self> {
+        response.extensions().get().copied()
+    }
+
+    /// Run `future` to completion, or fail with [`S3Error::RequestTimeout`] once `deadline`
+    /// has passed.
+    async fn bound<T>(
+        deadline: Option<Self>,
+        future: impl Future<Output = Result<T, S3Error>>,
+    ) -> Result<T, S3Error> {
+        match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.at, future)
+                .await
+                .map_err(|_| S3Error::RequestTimeout(deadline.timeout))?,
+            None => future.await,
+        }
+    }
+}
+
 // Temporary structure for making a request
 pub struct HyperRequest<'a> {
     pub bucket: &'a Bucket,
@@ -33,93 +103,181 @@ pub struct HyperRequest<'a> {
     pub datetime: OffsetDateTime,
 }
 
-#[async_trait::async_trait]
-impl<'a> Request for HyperRequest<'a> {
-    type Response = http::Response<Body>;
-    type HeaderMap = http::header::HeaderMap;
+/// Resolves hostnames with the bucket's [`Resolver`] if it has one, and with the system resolver
+/// otherwise.
+#[derive(Clone)]
+struct BucketResolver(Option<Resolver>);
 
-    async fn response(&self) -> Result<http::Response<Body>, S3Error> {
-        // Build headers
-        let headers = match self.headers() {
-            Ok(headers) => headers,
-            Err(e) => return Err(e),
-        };
-        let https_connector = HttpsConnector::new();
-        let client = Client::builder().build::<_, hyper::Body>(https_connector);
+type ResolveFuture =
+    Pin<Box<dyn Future<Output = Result<std::vec::IntoIter<SocketAddr>, std::io::Error>> + Send>>;
 
-        let method = match self.command.http_verb() {
-            HttpMethod::Delete => http::Method::DELETE,
-            HttpMethod::Get => http::Method::GET,
-            HttpMethod::Post => http::Method::POST,
-            HttpMethod::Put => http::Method::PUT,
-            HttpMethod::Head => http::Method::HEAD,
-        };
+impl Service<Name> for BucketResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = std::io::Error;
+    type Future = ResolveFuture;
 
-        let request = {
-            let mut request = http::Request::builder()
-                .method(method)
-                .uri(self.url()?.as_str());
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
 
-            for (header, value) in headers.iter() {
-                request = request.header(header, value);
+    fn call(&mut self, name: Name) -> Self::Future {
+        match &self.0 {
+            Some(resolver) => {
+                let addresses = resolver.resolve(name.as_str());
+                Box::pin(async move {
+                    if addresses.is_empty() {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("resolver returned no address for {}", name),
+                        ));
+                    }
+                    Ok(addresses.into_iter())
+                })
             }
+            None => {
+                let mut resolver = GaiResolver::new();
+                Box::pin(async move {
+                    let addresses = resolver.call(name).await?;
+                    Ok(addresses.collect::<Vec<_>>().into_iter())
+                })
+            }
+        }
+    }
+}
 
-            request.body(Body::from(self.request_body()))?
-        };
-        let span = span!(
-            Level::DEBUG,
-            "rust-s3-async",
-            bucket = self.bucket.name(),
-            command = self.command.to_string(),
-            path = self.path,
-            second = self.datetime.second(),
-            minute = self.datetime.minute(),
-            hour = self.datetime.hour(),
-            day = self.datetime.day(),
-            month = self.datetime.month() as u8,
-            year = self.datetime.year()
-        );
-        let _enter = span.enter();
-        let response = client.request(request).await?;
+fn http_connector(resolver: Option<Resolver>) -> HttpConnector<BucketResolver> {
+    let mut http = HttpConnector::new_with_resolver(BucketResolver(resolver));
+    http.enforce_http(false);
+    http
+}
 
-        event!(Level::DEBUG, status_code = response.status().as_u16(),);
+#[cfg(not(any(feature = "rustls-native-roots", feature = "rustls-webpki-roots")))]
+fn https_connector(
+    resolver: Option<Resolver>,
+) -> hyper_tls::HttpsConnector<HttpConnector<BucketResolver>> {
+    hyper_tls::HttpsConnector::new_with_connector(http_connector(resolver))
+}
 
-        if cfg!(feature = "fail-on-err") && !response.status().is_success() {
-            let status = response.status().as_u16();
-            let text =
-                String::from_utf8(hyper::body::to_bytes(response.into_body()).await?.into())?;
-            return Err(S3Error::HttpFailWithBody(status, text));
+#[cfg(any(feature = "rustls-native-roots", feature = "rustls-webpki-roots"))]
+fn https_connector(
+    resolver: Option<Resolver>,
+) -> hyper_rustls::HttpsConnector<HttpConnector<BucketResolver>> {
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(rustls_root_store())
+        .with_no_client_auth();
+    hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(config)
+        .https_or_http()
+        .enable_http1()
+        .enable_http2()
+        .wrap_connector(http_connector(resolver))
+}
+
+/// Trust anchors for the rustls connector, loaded from the system trust store with
+/// `rustls-native-roots` and from the bundled Mozilla roots with `rustls-webpki-roots`.
+/// With both features enabled, roots from both sources are trusted.
+#[cfg(any(feature = "rustls-native-roots", feature = "rustls-webpki-roots"))]
+fn rustls_root_store() -> rustls::RootCertStore {
+    #[allow(unused_mut)]
+    let mut roots = rustls::RootCertStore::empty();
+
+    #[cfg(feature = "rustls-native-roots")]
+    match rustls_native_certs::load_native_certs() {
+        Ok(certs) => {
+            for cert in certs {
+                if let Err(e) = roots.add(&rustls::Certificate(cert.0)) {
+                    event!(
+                        Level::WARN,
+                        "Skipping invalid native root certificate: {}",
+                        e
+                    );
+                }
+            }
         }
+        Err(e) => event!(
+            Level::WARN,
+            "Could not load native root certificates: {}",
+            e
+        ),
+    }
 
-        Ok(response)
+    #[cfg(feature = "rustls-webpki-roots")]
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+
+    roots
+}
+
+/// Response headers keyed by their lowercase names.
+fn headers_to_map(headers: &http::HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                v.to_str()
+                    .unwrap_or("could-not-decode-header-value")
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Frame `body` as `aws-chunked` content, each chunk prefixed with its hex length, ending with a
+/// trailer that carries the checksum of the whole body.
+fn aws_chunked(body: Body, checksum: ChecksumAlgorithm) -> Body {
+    let frames = futures::stream::unfold(
+        (body, Some(checksum.hasher())),
+        move |(mut body, hasher)| async move {
+            // Gone once the trailer is sent
+            let mut hasher = hasher?;
+            loop {
+                match body.next().await {
+                    // A zero length chunk would end the body early
+                    Some(Ok(chunk)) if chunk.is_empty() => continue,
+                    Some(Ok(chunk)) => {
+                        hasher.update(&chunk);
+                        let mut frame = format!("{:x}\r\n", chunk.len()).into_bytes();
+                        frame.extend_from_slice(&chunk);
+                        frame.extend_from_slice(b"\r\n");
+                        return Some((Ok(Bytes::from(frame)), (body, Some(hasher))));
+                    }
+                    Some(Err(e)) => return Some((Err(e), (body, None))),
+                    None => {
+                        let trailer = format!(
+                            "0\r\n{}:{}\r\n\r\n",
+                            checksum.header_name(),
+                            hasher.finalize()
+                        );
+                        return Some((Ok(Bytes::from(trailer)), (body, None)));
+                    }
+                }
+            }
+        },
+    );
+    Body::wrap_stream(frames)
+}
+
+#[async_trait::async_trait]
+impl<'a> Request for HyperRequest<'a> {
+    type Response = http::Response<Body>;
+    type HeaderMap = http::header::HeaderMap;
+
+    async fn response(&self) -> Result<http::Response<Body>, S3Error> {
+        let body = Bytes::from(self.request_body());
+        self.send(move || Some(Body::from(body.clone()))).await
     }
 
     async fn response_data(&self, etag: bool) -> Result<ResponseData, S3Error> {
         let response = self.response().await?;
-        let status_code = response.status().as_u16();
-        let mut headers = response.headers().clone();
-        let response_headers = headers
-            .clone()
-            .iter()
-            .map(|(k, v)| {
-                (
-                    k.to_string(),
-                    v.to_str()
-                        .unwrap_or("could-not-decode-header-value")
-                        .to_string(),
-                )
-            })
-            .collect::<HashMap<String, String>>();
-        let body_vec = if etag {
-            if let Some(etag) = headers.remove("ETag") {
-                Bytes::from(etag.to_str()?.to_string())
-            } else {
-                Bytes::from("")
-            }
-        } else {
-            hyper::body::to_bytes(response.into_body()).await?
-        };
-        Ok(ResponseData::new(body_vec, status_code, response_headers))
+        let decoder = self.content_decoder(&response);
+        Self::into_response_data(response, etag, decoder, self.bucket.max_response_size()).await
     }
 
     async fn response_data_to_writer<T: tokio::io::AsyncWrite + Send + Unpin>(
@@ -129,11 +287,24 @@ impl<'a> Request for HyperRequest<'a> {
         let response = self.response().await?;
 
         let status_code = response.status();
+        let deadline = Deadline::of(&response);
+        let mut decoder = self.content_decoder(&response);
         let mut stream = response.into_body().into_stream();
 
-        while let Some(item) = stream.next().await {
-            writer.write_all(&item?).await?;
-        }
+        Deadline::bound(deadline, async {
+            while let Some(item) = stream.next().await {
+                let chunk = item?;
+                match decoder.as_mut() {
+                    Some(decoder) => writer.write_all(&decoder.decode(&chunk)?).await?,
+                    None => writer.write_all(&chunk).await?,
+                }
+            }
+            if let Some(decoder) = decoder {
+                writer.write_all(&decoder.finish()?).await?;
+            }
+            Ok(())
+        })
+        .await?;
 
         Ok(status_code.as_u16())
     }
@@ -141,11 +312,13 @@ impl<'a> Request for HyperRequest<'a> {
     async fn response_data_to_stream(&self) -> Result<ResponseDataStream, S3Error> {
         let response = self.response().await?;
         let status_code = response.status();
+        let headers = headers_to_map(response.headers());
         let stream = response.into_body().into_stream().map_err(S3Error::Hyper);
 
         Ok(ResponseDataStream {
             bytes: Box::pin(stream),
             status_code: status_code.as_u16(),
+            headers,
         })
     }
 
@@ -164,7 +337,7 @@ impl<'a> Request for HyperRequest<'a> {
         self.bucket.clone()
     }
 
-    fn command(&self) -> Command {
+    fn command(&self) -> Command<'_> {
         self.command.clone()
     }
 
@@ -180,12 +353,430 @@ impl<'a> HyperRequest<'a> {
         command: Command<'a>,
     ) -> Result<HyperRequest<'a>, S3Error> {
         bucket.credentials_refresh()?;
+        bucket.validate_transfer_acceleration()?;
         Ok(Self {
             bucket,
             path,
             command,
-            datetime: OffsetDateTime::now_utc(),
+            datetime: bucket.now(),
+        })
+    }
+
+    /// Send the request and hand back hyper's response as is, body unread, for what the typed
+    /// methods don't expose, like trailers or unusual headers. The response type is specific to
+    /// this backend. With the `fail-on-err` feature, failed requests are still errors.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::command::Command;
+    /// use s3::creds::Credentials;
+    /// use s3::request::RequestImpl;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let request = RequestImpl::new(&bucket, "/test.file", Command::GetObject)?;
+    /// let response = request.raw_response().await?;
+    /// let restore = response.headers().get("x-amz-restore");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_response(&self) -> Result<http::Response<Body>, S3Error> {
+        self.response().await
+    }
+
+    /// Send the request, asking `body` for a fresh request body on every attempt. Retrying
+    /// stops early once `body` returns `None`, as is the case for bodies that can only be read
+    /// once.
+    async fn send<F>(&self, mut body: F) -> Result<http::Response<Body>, S3Error>
+    where
+        F: FnMut() -> Option<Body> + Send,
+    {
+        // Build headers
+        let mut headers = match self.headers() {
+            Ok(headers) => headers,
+            Err(e) => return Err(e),
+        };
+        let https_connector = https_connector(self.bucket.resolver());
+        let client = Client::builder().build::<_, hyper::Body>(https_connector);
+
+        let method = match self.command.http_verb() {
+            HttpMethod::Delete => http::Method::DELETE,
+            HttpMethod::Get => http::Method::GET,
+            HttpMethod::Post => http::Method::POST,
+            HttpMethod::Put => http::Method::PUT,
+            HttpMethod::Head => http::Method::HEAD,
+        };
+
+        let url = self.url()?;
+        let command = self.command.to_string();
+        let metrics = self.bucket.metrics();
+        let build_request = |body: Body, headers: &http::HeaderMap| {
+            let mut request = http::Request::builder()
+                .method(method.clone())
+                .uri(url.as_str());
+
+            for (header, value) in headers.iter() {
+                request = request.header(header, value);
+            }
+
+            request.body(body)
+        };
+        let span = span!(
+            Level::DEBUG,
+            "rust-s3-async",
+            bucket = self.bucket.name(),
+            command = command,
+            path = self.path,
+            second = self.datetime.second(),
+            minute = self.datetime.minute(),
+            hour = self.datetime.hour(),
+            day = self.datetime.day(),
+            month = self.datetime.month() as u8,
+            year = self.datetime.year()
+        );
+        let retry_policy = self.bucket.retry_policy();
+        let mut attempt = 0;
+        let mut skew_corrected = false;
+        let mut request_body = body();
+        let mut deadline;
+        let mut response = loop {
+            if let Some(metrics) = &metrics {
+                metrics.on_request(&command, &url);
+            }
+            let sent = Instant::now();
+            deadline = Deadline::after(self.bucket.request_timeout());
+            let response = client
+                .request(build_request(
+                    request_body.take().unwrap_or_default(),
+                    &headers,
+                )?)
+                .instrument(span.clone());
+            let mut response = Deadline::bound(deadline, async { Ok(response.await?) }).await?;
+
+            span.in_scope(|| event!(Level::DEBUG, status_code = response.status().as_u16(),));
+            if let Some(metrics) = &metrics {
+                metrics.on_response(
+                    &command,
+                    response.status().as_u16(),
+                    sent.elapsed(),
+                    HttpBody::size_hint(response.body()).exact(),
+                );
+            }
+
+            // Sign again as of the server's time, once, if it rejected ours as too far off
+            if self.bucket.is_clock_skew_correction()
+                && !skew_corrected
+                && response.status() == http::StatusCode::FORBIDDEN
+            {
+                let (parts, response_body) = response.into_parts();
+                let bytes = hyper::body::to_bytes(response_body).await?;
+                let server_time = parts
+                    .headers
+                    .get(DATE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| OffsetDateTime::parse(value, &Rfc2822).ok());
+                let too_skewed =
+                    String::from_utf8_lossy(&bytes).contains("<Code>RequestTimeTooSkewed</Code>");
+                response = http::Response::from_parts(parts, Body::from(bytes));
+                if let (Some(server_time), true) = (server_time, too_skewed) {
+                    request_body = body();
+                    if request_body.is_none() {
+                        break response;
+                    }
+                    self.bucket.correct_clock_skew(server_time);
+                    skew_corrected = true;
+                    let resigned = HyperRequest {
+                        bucket: self.bucket,
+                        path: self.path,
+                        command: self.command.clone(),
+                        datetime: self.bucket.now(),
+                    };
+                    headers = resigned.headers()?;
+                    span.in_scope(|| event!(Level::DEBUG, "clock skew corrected"));
+                    continue;
+                }
+            }
+
+            if attempt >= retry_policy.max_retries
+                || !RetryPolicy::is_retryable(response.status().as_u16())
+            {
+                break response;
+            }
+
+            request_body = body();
+            if request_body.is_none() {
+                break response;
+            }
+
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| parse_retry_after(value, self.bucket.now()));
+            let delay = retry_policy.delay(attempt, retry_after);
+            span.in_scope(|| event!(Level::DEBUG, attempt, delay_ms = delay.as_millis() as u64));
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            if let Some(metrics) = &metrics {
+                metrics.on_retry(&command, attempt);
+            }
+        };
+
+        if cfg!(feature = "fail-on-err")
+            && !response.status().is_success()
+            && !self.command.expects_status(response.status().as_u16())
+        {
+            let status = response.status().as_u16();
+            let headers = Box::new(response.headers().clone());
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+            let text = String::from_utf8_lossy(&body).into_owned();
+            return Err(S3Error::HttpFailWithBody(status, text, headers));
+        }
+
+        if let Some(deadline) = deadline {
+            response.extensions_mut().insert(deadline);
+        }
+        Ok(response)
+    }
+
+    /// Send the request with `body` streamed as its payload. The body can only be read once, so
+    /// the request is never retried.
+    pub async fn response_data_with_body(
+        &self,
+        body: Body,
+        etag: bool,
+    ) -> Result<ResponseData, S3Error> {
+        let body = match self.command {
+            Command::PutObjectReader {
+                checksum: Some(checksum),
+                ..
+            } => aws_chunked(body, checksum),
+            _ => body,
+        };
+        let mut body = Some(body);
+        let response = self.send(move || body.take()).await?;
+        Self::into_response_data(response, etag, None, self.bucket.max_response_size()).await
+    }
+
+    /// Write the response body to the file at `path`, frame by frame as it arrives. Nothing is
+    /// written unless the request succeeded, for a [`Command::GetObjectRange`] only if the
+    /// response is partial content starting at the first byte of the range.
+    ///
+    /// Without an `offset`, the body goes to a temporary file next to `path` that replaces it
+    /// once complete, so a failed download leaves an existing file alone. With one, the body is
+    /// written in place from `offset`, creating the file if needed. The file is grown up front
+    /// to hold the whole body when its length is known, it's never shrunk, so ranges of an
+    /// object can be downloaded concurrently into the same file.
+    ///
+    /// Fails with an [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) IO error if the body
+    /// ends short of its `Content-Length`.
+    pub async fn response_data_to_file(
+        &self,
+        path: &Path,
+        offset: Option<u64>,
+    ) -> Result<u16, S3Error> {
+        let response = self.response().await?;
+        let status_code = response.status().as_u16();
+        let range_error = match self.command {
+            Command::GetObjectRange { start, .. } => {
+                let content_range = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let first = content_range
+                    .as_deref()
+                    .and_then(parse_content_range)
+                    .map(|(first, _)| first);
+                (status_code != 206 || first != Some(start)).then(|| {
+                    S3Error::RangeNotHonored(format!(
+                        "requested bytes from {}, got status {} with Content-Range {:?}",
+                        start, status_code, content_range
+                    ))
+                })
+            }
+            _ => None,
+        };
+        if status_code >= 300 || range_error.is_some() {
+            let response_data =
+                Self::into_response_data(response, false, None, self.bucket.max_response_size())
+                    .await?;
+            return match range_error {
+                Some(range_error) if status_code < 300 => Err(range_error),
+                _ => Err(error_from_response_data(response_data)?),
+            };
+        }
+
+        match offset {
+            Some(offset) => {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(path)
+                    .await?;
+                self.write_body(response, &mut file, offset).await?;
+            }
+            None => {
+                let partial_path = partial_path(path);
+                let mut file = tokio::fs::File::create(&partial_path).await?;
+                let written = self.write_body(response, &mut file, 0).await;
+                drop(file);
+                let renamed = match written {
+                    Ok(()) => tokio::fs::rename(&partial_path, path)
+                        .await
+                        .map_err(S3Error::from),
+                    Err(error) => Err(error),
+                };
+                if let Err(error) = renamed {
+                    let _ = tokio::fs::remove_file(&partial_path).await;
+                    return Err(error);
+                }
+            }
+        }
+        Ok(status_code)
+    }
+
+    /// Write the body of `response` to `file`, starting at `offset`.
+    async fn write_body(
+        &self,
+        response: http::Response<Body>,
+        file: &mut tokio::fs::File,
+        offset: u64,
+    ) -> Result<(), S3Error> {
+        let mut decoder = self.content_decoder(&response);
+        // The length of an inflated body isn't known before it's inflated
+        let expected = match decoder {
+            Some(_) => None,
+            None => HttpBody::size_hint(response.body()).exact(),
+        };
+        if let Some(expected) = expected {
+            if file.metadata().await?.len() < offset + expected {
+                file.set_len(offset + expected).await?;
+            }
+        }
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        let deadline = Deadline::of(&response);
+        let mut stream = response.into_body().into_stream();
+        let written = Deadline::bound(deadline, async {
+            let mut written = 0;
+            while let Some(item) = stream.next().await {
+                let chunk = item?;
+                let chunk = match decoder.as_mut() {
+                    Some(decoder) => Bytes::from(decoder.decode(&chunk)?),
+                    None => chunk,
+                };
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+            }
+            if let Some(decoder) = decoder {
+                let chunk = decoder.finish()?;
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+            }
+            // Wait for the last write to reach the file
+            file.flush().await?;
+            Ok(written)
         })
+        .await?;
+
+        match expected {
+            Some(expected) if written != expected => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("expected {} bytes, got {}", expected, written),
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decoder for the body of `response` if the bucket is set to inflate downloaded objects
+    /// and the object is `gzip` or `deflate` encoded.
+    fn content_decoder(&self, response: &http::Response<Body>) -> Option<ContentDecoder> {
+        if !self.bucket.is_auto_decompress()
+            || !matches!(
+                self.command,
+                Command::GetObject
+                    | Command::GetObjectAs { .. }
+                    | Command::GetObjectWithOptions { .. }
+            )
+        {
+            return None;
+        }
+        let content_encoding = response.headers().get(CONTENT_ENCODING)?.to_str().ok()?;
+        ContentDecoder::new(content_encoding)
+    }
+
+    async fn into_response_data(
+        response: http::Response<Body>,
+        etag: bool,
+        decoder: Option<ContentDecoder>,
+        max_response_size: Option<usize>,
+    ) -> Result<ResponseData, S3Error> {
+        let status_code = response.status().as_u16();
+        let deadline = Deadline::of(&response);
+        let mut headers = response.headers().clone();
+        let response_headers = headers_to_map(&headers);
+        let body_vec = if etag {
+            if let Some(etag) = headers.remove("ETag") {
+                Bytes::from(etag.to_str()?.to_string())
+            } else {
+                Bytes::from("")
+            }
+        } else {
+            let body = Deadline::bound(deadline, async {
+                Ok(match max_response_size {
+                    Some(limit) => Self::limited_body(response.into_body(), limit).await?,
+                    None => hyper::body::to_bytes(response.into_body()).await?,
+                })
+            })
+            .await?;
+            match decoder {
+                Some(mut decoder) => {
+                    let mut decoded = decoder.decode(&body)?;
+                    decoded.extend(decoder.finish()?);
+                    // Inflating can take a small body well over the limit
+                    if let Some(limit) = max_response_size {
+                        if decoded.len() > limit {
+                            return Err(S3Error::ResponseTooLarge { limit });
+                        }
+                    }
+                    Bytes::from(decoded)
+                }
+                None => body,
+            }
+        };
+        Ok(ResponseData::new(body_vec, status_code, response_headers))
+    }
+
+    /// Buffer `body`, giving up as soon as it's longer than `limit` bytes, or up front if its
+    /// `Content-Length` already is.
+    async fn limited_body(body: Body, limit: usize) -> Result<Bytes, S3Error> {
+        let too_large = || S3Error::ResponseTooLarge { limit };
+        if HttpBody::size_hint(&body).lower() > limit as u64 {
+            return Err(too_large());
+        }
+        let mut buffer = Vec::new();
+        let mut stream = body.into_stream();
+        while let Some(item) = stream.next().await {
+            let chunk = item?;
+            if buffer.len() + chunk.len() > limit {
+                return Err(too_large());
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        Ok(Bytes::from(buffer))
     }
 }
 
@@ -193,10 +784,17 @@ impl<'a> HyperRequest<'a> {
 mod tests {
     use crate::bucket::Bucket;
     use crate::command::Command;
+    use crate::error::S3Error;
+    use crate::metrics::S3Metrics;
     use crate::request::tokio_backend::HyperRequest;
     use crate::request::Request;
+    use crate::retry::RetryPolicy;
+    use crate::test_utils::{http_response, serve_once, serve_sequence};
+    use crate::Region;
     use awscreds::Credentials;
     use http::header::{HOST, RANGE};
+    use std::sync::Arc;
+    use std::time::Duration;
 
     // Fake keys - otherwise using Credentials::default will use actual user
     // credentials if they exist.
@@ -206,6 +804,78 @@ mod tests {
         Credentials::new(Some(access_key), Some(secert_key), None, None, None).unwrap()
     }
 
+    #[tokio::test]
+    async fn test_clock() {
+        let (bucket, server) = serve_once(http_response("200 OK", &[], "content"));
+        let bucket =
+            bucket.with_clock(Arc::new(|| time::macros::datetime!(2013-05-24 1:02:03 UTC)));
+        bucket.get_object("/test.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert!(request.head.contains("x-amz-date: 20130524t010203z\r\n"));
+        assert!(request
+            .head
+            .contains("/20130524/custom-region/s3/aws4_request"));
+
+        let url = bucket.presign_get("/test.file", 60, None).unwrap();
+        assert!(url.contains("X-Amz-Date=20130524T010203Z"));
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_correction() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response(
+                "403 Forbidden",
+                &[("Date", "Fri, 24 May 2013 00:00:00 GMT")],
+                "<Error><Code>RequestTimeTooSkewed</Code>\
+                 <Message>The difference between the request time and the current time is too large.</Message></Error>",
+            ),
+            http_response("200 OK", &[], "content"),
+            http_response("200 OK", &[], "content"),
+        ]);
+        let bucket = bucket
+            .with_clock(Arc::new(
+                || time::macros::datetime!(2013-05-23 23:00:00 UTC),
+            ))
+            .with_clock_skew_correction(true);
+
+        let response = bucket.get_object("/test.file").await.unwrap();
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(response.as_slice(), b"content");
+        // The offset sticks for later requests
+        bucket.get_object("/test.file").await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .head
+            .contains("x-amz-date: 20130523t230000z\r\n"));
+        assert!(requests[1]
+            .head
+            .contains("x-amz-date: 20130524t000000z\r\n"));
+        assert!(requests[2]
+            .head
+            .contains("x-amz-date: 20130524t000000z\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_response() {
+        let (bucket, server) = serve_once(http_response(
+            "200 OK",
+            &[("x-amz-restore", "ongoing-request=\"true\"")],
+            "body",
+        ));
+        let request = HyperRequest::new(&bucket, "/test.file", Command::GetObject).unwrap();
+        let response = request.raw_response().await.unwrap();
+        server.join().unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["x-amz-restore"],
+            "ongoing-request=\"true\""
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body.as_ref(), b"body");
+    }
+
     #[test]
     fn url_uses_https_by_default() {
         let region = "custom-region".parse().unwrap();
@@ -268,6 +938,177 @@ mod tests {
         assert_eq!(*host, "custom-region".to_string());
     }
 
+    #[test]
+    fn url_uses_accelerate_endpoint() {
+        let region = "eu-west-1".parse().unwrap();
+        let bucket = Bucket::new("my-accelerated-bucket", region, fake_credentials())
+            .unwrap()
+            .with_transfer_acceleration(true);
+        let path = "/my-first/path";
+        let request = HyperRequest::new(&bucket, path, Command::GetObject).unwrap();
+
+        assert_eq!(
+            request.url().unwrap().as_str(),
+            "https://my-accelerated-bucket.s3-accelerate.amazonaws.com/my-first/path"
+        );
+        let headers = request.headers().unwrap();
+        assert_eq!(
+            *headers.get(HOST).unwrap(),
+            "my-accelerated-bucket.s3-accelerate.amazonaws.com"
+        );
+
+        let bucket = bucket.with_dualstack(true);
+        let request = HyperRequest::new(&bucket, path, Command::GetObject).unwrap();
+        let headers = request.headers().unwrap();
+        assert_eq!(
+            *headers.get(HOST).unwrap(),
+            "my-accelerated-bucket.s3-accelerate.dualstack.amazonaws.com"
+        );
+
+        let bucket = bucket.with_transfer_acceleration(false);
+        let request = HyperRequest::new(&bucket, path, Command::GetObject).unwrap();
+        let headers = request.headers().unwrap();
+        assert_eq!(
+            *headers.get(HOST).unwrap(),
+            "my-accelerated-bucket.s3.dualstack.eu-west-1.amazonaws.com"
+        );
+    }
+
+    #[test]
+    fn accelerate_endpoint_needs_dns_compatible_subdomain_style() {
+        let region = "eu-west-1".parse().unwrap();
+        let bucket = Bucket::new("my.dotted.bucket", region, fake_credentials())
+            .unwrap()
+            .with_transfer_acceleration(true);
+        assert!(HyperRequest::new(&bucket, "/path", Command::GetObject).is_err());
+
+        let region = "eu-west-1".parse().unwrap();
+        let bucket = Bucket::new("my-bucket", region, fake_credentials())
+            .unwrap()
+            .with_path_style()
+            .with_transfer_acceleration(true);
+        assert!(HyperRequest::new(&bucket, "/path", Command::GetObject).is_err());
+    }
+
+    #[tokio::test]
+    async fn resolver_overrides_dns() {
+        let (bucket, server) = serve_once(http_response("200 OK", &[], "resolved"));
+        let port = bucket
+            .region()
+            .host()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_string();
+        let region = Region::Custom {
+            region: "custom-region".to_owned(),
+            endpoint: format!("http://s3.fake-host.invalid:{}", port),
+        };
+        let bucket = Bucket::new("rust-s3-test", region, fake_credentials())
+            .unwrap()
+            .with_path_style()
+            .with_resolver(|host| {
+                assert_eq!(host, "s3.fake-host.invalid");
+                vec!["127.0.0.1:0".parse().unwrap()]
+            });
+
+        let response_data = bucket.get_object("/resolved.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert_eq!(response_data.as_slice(), b"resolved");
+        assert!(request
+            .head
+            .contains(&format!("host: s3.fake-host.invalid:{}", port)));
+    }
+
+    #[tokio::test]
+    async fn host_header_overrides_endpoint_host() {
+        let (bucket, server) = serve_once(http_response("200 OK", &[], "proxied"));
+        let connect_host = bucket.host();
+        let bucket = bucket.with_host_header("s3.internal.example");
+
+        let request = HyperRequest::new(&bucket, "/proxied.file", Command::GetObject).unwrap();
+        assert_eq!(request.headers().unwrap()[HOST], "s3.internal.example");
+        assert!(request.url().unwrap().as_str().contains(&connect_host));
+
+        let response_data = bucket.get_object("/proxied.file").await.unwrap();
+        let request = server.join().unwrap();
+        assert_eq!(response_data.as_slice(), b"proxied");
+        assert!(request.head.contains("host: s3.internal.example\r\n"));
+        assert!(!request.head.contains(&format!("host: {}", connect_host)));
+        let signed_headers = request.head.split("signedheaders=").nth(1).unwrap();
+        let signed_headers = signed_headers.split(',').next().unwrap();
+        assert!(signed_headers.split(';').any(|header| header == "host"));
+    }
+
+    #[derive(Default)]
+    struct RecordingMetrics(std::sync::Mutex<Vec<String>>);
+
+    impl S3Metrics for RecordingMetrics {
+        fn on_request(&self, command: &str, url: &url::Url) {
+            let event = format!("request {} {}", command, url.path());
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn on_response(&self, command: &str, status: u16, _: Duration, bytes: Option<u64>) {
+            let event = format!("response {} {} {:?}", command, status, bytes);
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn on_retry(&self, command: &str, attempt: u32) {
+            let event = format!("retry {} {}", command, attempt);
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn metrics_hooks_fire_per_attempt() {
+        let (bucket, server) = serve_sequence(vec![
+            http_response("503 Service Unavailable", &[], "busy"),
+            http_response("200 OK", &[], "measured"),
+        ]);
+        let metrics = Arc::new(RecordingMetrics::default());
+        let bucket = bucket
+            .with_retry_policy(RetryPolicy::new(1, Duration::ZERO, Duration::ZERO))
+            .with_metrics(metrics.clone());
+
+        let response_data = bucket.get_object("/measured.file").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(response_data.as_slice(), b"measured");
+        assert_eq!(
+            *metrics.0.lock().unwrap(),
+            [
+                "request GetObject /rust-s3-test/measured.file",
+                "response GetObject 503 Some(4)",
+                "retry GetObject 1",
+                "request GetObject /rust-s3-test/measured.file",
+                "response GetObject 200 Some(8)",
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_waits_for_retry_after() {
+        let (mut bucket, server) = serve_sequence(vec![
+            http_response("503 Service Unavailable", &[("Retry-After", "2")], "busy"),
+            http_response("200 OK", &[], "patient"),
+        ]);
+        // The paused clock jumps ahead to the next timer while waiting for the server, it
+        // would be the timeout's
+        bucket.set_request_timeout(None);
+        let bucket = bucket.with_retry_policy(RetryPolicy::new(
+            1,
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+        ));
+
+        let started = tokio::time::Instant::now();
+        let response_data = bucket.get_object("/patient.file").await.unwrap();
+        server.join().unwrap();
+        assert_eq!(response_data.as_slice(), b"patient");
+        // Longer than the backoff allows, as the server asked
+        assert!(started.elapsed() >= Duration::from_secs(2));
+    }
+
     #[test]
     fn test_get_object_range_header() {
         let region = "http://custom-region".parse().unwrap();
@@ -302,4 +1143,119 @@ mod tests {
         let range = headers.get(RANGE).unwrap();
         assert_eq!(range, "bytes=0-1");
     }
+
+    #[test]
+    fn test_list_multipart_uploads_markers_query() {
+        let region = "http://custom-region".parse().unwrap();
+        let bucket = Bucket::new("my-second-bucket", region, fake_credentials())
+            .unwrap()
+            .with_path_style();
+
+        let request = HyperRequest::new(
+            &bucket,
+            "/",
+            Command::ListMultipartUploads {
+                prefix: Some("some/prefix/"),
+                delimiter: Some("/"),
+                key_marker: Some("my-movie.m2ts".to_string()),
+                upload_id_marker: Some("VXBsb2FkIElE".to_string()),
+                max_uploads: None,
+            },
+        )
+        .unwrap();
+        let url = request.url().unwrap();
+        let query = url.query_pairs().collect::<Vec<_>>();
+
+        assert!(url.as_str().contains("?uploads"));
+        assert!(query.contains(&("prefix".into(), "some/prefix/".into())));
+        assert!(query.contains(&("delimiter".into(), "/".into())));
+        assert!(query.contains(&("key-marker".into(), "my-movie.m2ts".into())));
+        assert!(query.contains(&("upload-id-marker".into(), "VXBsb2FkIElE".into())));
+
+        let request = HyperRequest::new(
+            &bucket,
+            "/",
+            Command::ListMultipartUploads {
+                prefix: None,
+                delimiter: None,
+                key_marker: None,
+                upload_id_marker: None,
+                max_uploads: None,
+            },
+        )
+        .unwrap();
+        let url = request.url().unwrap();
+
+        assert!(!url.as_str().contains("key-marker"));
+        assert!(!url.as_str().contains("upload-id-marker"));
+    }
+
+    #[cfg(any(feature = "rustls-native-roots", feature = "rustls-webpki-roots"))]
+    #[test]
+    fn test_rustls_root_store_is_not_empty() {
+        assert!(!super::rustls_root_store().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_timeout_applies() {
+        use std::io::Read;
+        use std::time::Duration;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let region = Region::Custom {
+            region: "custom-region".to_owned(),
+            endpoint: format!("http://{}", listener.local_addr().unwrap()),
+        };
+        // Read the request, but never answer it
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 4096];
+            let _ = stream.read(&mut buffer);
+            std::thread::sleep(Duration::from_millis(500));
+        });
+        let bucket = Bucket::new("rust-s3-test", region, fake_credentials())
+            .unwrap()
+            .with_path_style()
+            .with_request_timeout(Duration::from_millis(100));
+
+        let error = bucket.get_object("/slow.file").await.unwrap_err();
+        assert!(matches!(
+            error,
+            S3Error::RequestTimeout(timeout) if timeout == Duration::from_millis(100)
+        ));
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn request_timeout_applies_to_body() {
+        use std::io::{Read, Write};
+        use std::time::Duration;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let region = Region::Custom {
+            region: "custom-region".to_owned(),
+            endpoint: format!("http://{}", listener.local_addr().unwrap()),
+        };
+        // Answer with the headers and part of the body, then stall
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 4096];
+            let _ = stream.read(&mut buffer);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nsome of it")
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(500));
+        });
+        let bucket = Bucket::new("rust-s3-test", region, fake_credentials())
+            .unwrap()
+            .with_path_style()
+            .with_request_timeout(Duration::from_millis(100));
+
+        let error = bucket.get_object("/slow.file").await.unwrap_err();
+        assert!(matches!(
+            error,
+            S3Error::RequestTimeout(timeout) if timeout == Duration::from_millis(100)
+        ));
+        server.join().unwrap();
+    }
 }
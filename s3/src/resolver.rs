@@ -0,0 +1,54 @@
+//! Overriding how the endpoint host is resolved
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Resolves a hostname to the addresses to connect to, in place of the system DNS resolver.
+///
+/// The port of the returned addresses is ignored, the port of the endpoint is used instead.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::net::SocketAddr;
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+///
+/// let bucket_name = "rust-s3-test";
+/// let region = "us-east-1".parse().unwrap();
+/// let credentials = Credentials::default().unwrap();
+/// let address: SocketAddr = "10.0.0.12:443".parse().unwrap();
+///
+/// let bucket = Bucket::new(bucket_name, region, credentials)
+///     .unwrap()
+///     .with_resolver(move |_host| vec![address]);
+/// ```
+#[derive(Clone)]
+pub struct Resolver(Arc<ResolveFn>);
+
+type ResolveFn = dyn Fn(&str) -> Vec<SocketAddr> + Send + Sync;
+
+impl Resolver {
+    pub fn new<F>(resolve: F) -> Self
+    where
+        F: Fn(&str) -> Vec<SocketAddr> + Send + Sync + 'static,
+    {
+        Resolver(Arc::new(resolve))
+    }
+
+    /// Always connect to `address`, whatever the host.
+    pub fn fixed(address: SocketAddr) -> Self {
+        Resolver::new(move |_| vec![address])
+    }
+
+    pub fn resolve(&self, host: &str) -> Vec<SocketAddr> {
+        (self.0)(host)
+    }
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Resolver")
+    }
+}
@@ -0,0 +1,129 @@
+//! Retrying of requests that failed with a transient error
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+
+/// Controls how often, and how long apart, requests failing with a transient status code
+/// (`429`, `500`, `502`, `503` or `504`) are retried.
+///
+/// The delay between attempts doubles with every retry, starting at `base_delay` and capped at
+/// `max_delay`. When the response carries a `Retry-After` header, the request is retried no
+/// sooner than it asks for, even if that is later than `max_delay`: the server knows best when
+/// it can take the request again. Keep `max_retries` low, or await the call with a timeout of
+/// your own, to bound how long a busy server can hold it up.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use s3::retry::RetryPolicy;
+///
+/// let bucket_name = "rust-s3-test";
+/// let region = "us-east-1".parse().unwrap();
+/// let credentials = Credentials::default().unwrap();
+///
+/// let bucket = Bucket::new(bucket_name, region, credentials)
+///     .unwrap()
+///     .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(100), Duration::from_secs(5)));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Requests are not retried.
+    fn default() -> Self {
+        RetryPolicy::none()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// Never retry a request.
+    pub fn none() -> Self {
+        Self::new(0, Duration::ZERO, Duration::ZERO)
+    }
+
+    /// Whether a response with this status code is worth retrying.
+    pub fn is_retryable(status_code: u16) -> bool {
+        matches!(status_code, 429 | 500 | 502 | 503 | 504)
+    }
+
+    /// Delay before retry number `attempt` (starting at 0), honoring `retry_after` if the
+    /// server asked for one. `retry_after` isn't capped at `max_delay`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        match retry_after {
+            Some(retry_after) => backoff.max(retry_after),
+            None => backoff,
+        }
+    }
+}
+
+/// Parse a `Retry-After` header value, given either as a number of seconds or as an HTTP-date,
+/// into the time left to wait from `now`.
+pub fn parse_retry_after(value: &str, now: OffsetDateTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    Some(Duration::try_from(date - now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = datetime!(2015-10-21 07:28:00 UTC);
+
+        assert_eq!(parse_retry_after("2", now), Some(Duration::from_secs(2)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:05 GMT", now),
+            Some(Duration::from_secs(5))
+        );
+        // A date in the past means there is no need to wait
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_retry_delay_honors_retry_after() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(100), Duration::from_secs(1));
+        let now = datetime!(2015-10-21 07:28:00 UTC);
+
+        assert_eq!(policy.delay(0, None), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, None), Duration::from_millis(400));
+        assert_eq!(policy.delay(10, None), Duration::from_secs(1));
+
+        let retry_after = parse_retry_after("2", now);
+        assert!(policy.delay(0, retry_after) >= Duration::from_secs(2));
+        let retry_after = parse_retry_after("Wed, 21 Oct 2015 07:28:03 GMT", now);
+        assert!(policy.delay(0, retry_after) >= Duration::from_secs(3));
+    }
+}
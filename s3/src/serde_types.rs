@@ -1,4 +1,46 @@
+use crate::checksum::ChecksumAlgorithm;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Define an enum sent and received as the text of an element. quick-xml would write the
+/// variants out as elements, S3 wants them as text.
+macro_rules! xml_text_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $text:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $text,)+
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match String::deserialize(deserializer)?.as_str() {
+                    $($text => Ok($name::$variant),)+
+                    other => Err(serde::de::Error::unknown_variant(other, &[$($text),+])),
+                }
+            }
+        }
+    };
+}
 
 #[derive(Deserialize, Debug)]
 pub struct InitiateMultipartUploadResponse {
@@ -11,9 +53,10 @@ pub struct InitiateMultipartUploadResponse {
 }
 
 /// Owner information for the object
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Owner {
     #[serde(rename = "DisplayName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     /// Object owner's name.
     pub display_name: Option<String>,
     #[serde(rename = "ID")]
@@ -45,6 +88,36 @@ pub struct Object {
     #[serde(rename = "Size")]
     /// Size in bytes of the object.
     pub size: u64,
+    #[serde(rename = "ChecksumAlgorithm", default)]
+    /// Algorithms of the additional checksums stored with the object, e.g. `SHA256`.
+    pub checksum_algorithm: Vec<String>,
+    #[serde(rename = "RestoreStatus")]
+    /// Restore state of an archived object, only returned when asked for.
+    pub restore_status: Option<RestoreStatus>,
+}
+
+/// Restore state of an object archived to Glacier or Deep Archive.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RestoreStatus {
+    #[serde(
+        rename = "IsRestoreInProgress",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    /// A restore is under way.
+    pub is_restore_in_progress: bool,
+    #[serde(rename = "RestoreExpiryDate")]
+    /// When the restored copy expires, once the restore completed.
+    pub restore_expiry_date: Option<DateTime>,
+}
+
+impl Object {
+    /// `last_modified` parsed as the RFC 3339 timestamp S3 sends.
+    pub fn last_modified_datetime(&self) -> Result<time::OffsetDateTime, crate::error::S3Error> {
+        Ok(time::OffsetDateTime::parse(
+            &self.last_modified,
+            &time::format_description::well_known::Rfc3339,
+        )?)
+    }
 }
 
 /// An individual upload in a `ListMultipartUploadsResult`
@@ -85,11 +158,11 @@ impl fmt::Display for CompleteMultipartUploadData {
 
 impl CompleteMultipartUploadData {
     pub fn len(&self) -> usize {
-        self.to_string().as_bytes().len()
+        self.to_string().len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.to_string().as_bytes().len() == 0
+        self.to_string().len() == 0
     }
 }
 
@@ -104,6 +177,10 @@ pub struct Part {
     pub part_number: u32,
     #[serde(rename = "ETag")]
     pub etag: String,
+    /// Size of the uploaded part in bytes, when known, to validate the parts before completing
+    /// the upload
+    #[serde(skip)]
+    pub size: Option<usize>,
 }
 
 impl fmt::Display for Part {
@@ -115,9 +192,107 @@ impl fmt::Display for Part {
     }
 }
 
+/// An attribute to ask for with
+/// [`Bucket::get_object_attributes`](crate::Bucket::get_object_attributes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectAttribute {
+    ETag,
+    StorageClass,
+    ObjectSize,
+    ObjectParts,
+    Checksum,
+}
+
+impl fmt::Display for ObjectAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectAttribute::ETag => write!(f, "ETag"),
+            ObjectAttribute::StorageClass => write!(f, "StorageClass"),
+            ObjectAttribute::ObjectSize => write!(f, "ObjectSize"),
+            ObjectAttribute::ObjectParts => write!(f, "ObjectParts"),
+            ObjectAttribute::Checksum => write!(f, "Checksum"),
+        }
+    }
+}
+
+/// The result of GetObjectAttributes, with the attributes that were asked for.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct GetObjectAttributesOutput {
+    #[serde(rename = "ETag")]
+    pub e_tag: Option<String>,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: Option<String>,
+    #[serde(rename = "ObjectSize")]
+    pub object_size: Option<u64>,
+    #[serde(rename = "ObjectParts")]
+    pub object_parts: Option<ObjectParts>,
+    #[serde(rename = "Checksum")]
+    pub checksum: Option<ObjectChecksum>,
+}
+
+/// The additional checksum of an object, as listed by GetObjectAttributes. For objects uploaded
+/// in parts, it's the [composite](crate::checksum::ChecksumAlgorithm::composite) of the part
+/// checksums.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ObjectChecksum {
+    #[serde(rename = "ChecksumCRC32")]
+    pub checksum_crc32: Option<String>,
+}
+
+impl ObjectChecksum {
+    /// The checksum computed with `algorithm`, if the object has one.
+    pub fn get(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        match algorithm {
+            ChecksumAlgorithm::Crc32 => self.checksum_crc32.as_deref(),
+        }
+    }
+}
+
+/// The parts of an object that was uploaded in parts, one page of them at a time.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct ObjectParts {
+    #[serde(rename = "PartsCount")]
+    /// Number of parts of the object.
+    pub parts_count: Option<u32>,
+    #[serde(
+        default,
+        rename = "IsTruncated",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    /// Whether more parts follow, starting after `next_part_number_marker`.
+    pub is_truncated: bool,
+    #[serde(rename = "NextPartNumberMarker")]
+    pub next_part_number_marker: Option<u32>,
+    #[serde(rename = "Part", default)]
+    /// The parts on this page. S3 only lists them for objects uploaded with an additional
+    /// checksum.
+    pub parts: Vec<ObjectPart>,
+}
+
+/// A part of an object, as listed by GetObjectAttributes.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ObjectPart {
+    #[serde(rename = "PartNumber")]
+    pub part_number: u32,
+    #[serde(rename = "Size")]
+    /// Size of the part in bytes.
+    pub size: u64,
+    #[serde(rename = "ChecksumCRC32")]
+    pub checksum_crc32: Option<String>,
+}
+
+impl ObjectPart {
+    /// The checksum of the part computed with `algorithm`, if it has one.
+    pub fn checksum(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        match algorithm {
+            ChecksumAlgorithm::Crc32 => self.checksum_crc32.as_deref(),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct BucketLocationResult {
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", default)]
     pub region: String,
 }
 
@@ -172,6 +347,53 @@ pub struct ListBucketResult {
     pub common_prefixes: Option<Vec<CommonPrefix>>,
 }
 
+impl ListBucketResult {
+    /// Iterate over the objects returned in this page of the listing.
+    pub fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.contents.iter()
+    }
+
+    /// Iterate over the common prefixes ("directories") returned in this page of the listing.
+    pub fn prefixes(&self) -> impl Iterator<Item = &str> {
+        self.common_prefixes
+            .iter()
+            .flatten()
+            .map(|common_prefix| common_prefix.prefix.as_str())
+    }
+
+    /// Flatten the pages returned by [`crate::bucket::Bucket::list`] into the object keys and
+    /// common prefixes they contain, de-duplicated and in listing order.
+    pub fn flatten(results: &[ListBucketResult]) -> ListBucketEntries {
+        let mut entries = ListBucketEntries::default();
+        let mut seen_keys = HashSet::new();
+        let mut seen_prefixes = HashSet::new();
+
+        for result in results {
+            for object in result.objects() {
+                if seen_keys.insert(object.key.as_str()) {
+                    entries.keys.push(object.key.clone());
+                }
+            }
+            for prefix in result.prefixes() {
+                if seen_prefixes.insert(prefix) {
+                    entries.common_prefixes.push(prefix.to_string());
+                }
+            }
+        }
+
+        entries
+    }
+}
+
+/// Object keys and common prefixes collected across all pages of a bucket listing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListBucketEntries {
+    /// Keys of the objects directly under the listed prefix.
+    pub keys: Vec<String>,
+    /// Common prefixes, i.e. the "directories" under the listed prefix.
+    pub common_prefixes: Vec<String>,
+}
+
 /// The parsed result of a s3 bucket listing of uploads
 #[derive(Deserialize, Debug, Clone)]
 pub struct ListMultipartUploadsResult {
@@ -191,6 +413,14 @@ pub struct ListMultipartUploadsResult {
     #[serde(rename = "KeyMarker")]
     /// Indicates where in the bucket listing begins.
     pub marker: Option<String>,
+    #[serde(rename = "NextUploadIdMarker")]
+    /// When the response is truncated, the upload ID to pass as `upload-id-marker` together
+    /// with `next_marker` in the subsequent request. Required to continue correctly when many
+    /// uploads share the same key.
+    pub next_upload_id_marker: Option<String>,
+    #[serde(rename = "UploadIdMarker")]
+    /// Together with `marker`, indicates where in the listing of uploads the response begins.
+    pub upload_id_marker: Option<String>,
     #[serde(rename = "EncodingType")]
     /// Specifies the encoding method to used
     pub encoding_type: Option<String>,
@@ -311,6 +541,12 @@ pub struct HeadObjectResult {
     #[serde(rename = "WebsiteRedirectLocation")]
     /// If the bucket is configured as a website, redirects requests for this object to another object in the same bucket or to an external URL. Amazon S3 stores the value of this header in the object metadata.
     pub website_redirect_location: Option<String>,
+    /// `x-amz-request-id` of the response, useful when contacting support.
+    #[serde(skip)]
+    pub request_id: Option<String>,
+    /// `x-amz-id-2` (extended request id) of the response.
+    #[serde(skip)]
+    pub extended_request_id: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -375,9 +611,864 @@ impl CorsRule {
     }
 }
 
+/// Default server-side encryption applied to new objects in a bucket, as sent to and returned
+/// by the `?encryption` subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "ServerSideEncryptionConfiguration")]
+pub struct ServerSideEncryptionConfiguration {
+    #[serde(rename = "Rule")]
+    pub rules: Vec<ServerSideEncryptionRule>,
+}
+
+impl ServerSideEncryptionConfiguration {
+    pub fn new(rules: Vec<ServerSideEncryptionRule>) -> Self {
+        ServerSideEncryptionConfiguration { rules }
+    }
+
+    /// Encrypt new objects with S3 managed keys (SSE-S3).
+    pub fn aes256() -> Self {
+        Self::new(vec![ServerSideEncryptionRule::new(
+            ApplyServerSideEncryptionByDefault::new("AES256", None),
+            None,
+        )])
+    }
+
+    /// Encrypt new objects with a KMS key (SSE-KMS), the AWS managed `aws/s3` key if
+    /// `kms_master_key_id` is `None`.
+    pub fn kms(kms_master_key_id: Option<String>, bucket_key_enabled: bool) -> Self {
+        Self::new(vec![ServerSideEncryptionRule::new(
+            ApplyServerSideEncryptionByDefault::new("aws:kms", kms_master_key_id),
+            Some(bucket_key_enabled),
+        )])
+    }
+}
+
+impl fmt::Display for ServerSideEncryptionConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerSideEncryptionRule {
+    #[serde(rename = "ApplyServerSideEncryptionByDefault")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_server_side_encryption_by_default: Option<ApplyServerSideEncryptionByDefault>,
+    #[serde(rename = "BucketKeyEnabled")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket_key_enabled: Option<bool>,
+}
+
+impl ServerSideEncryptionRule {
+    pub fn new(
+        apply_server_side_encryption_by_default: ApplyServerSideEncryptionByDefault,
+        bucket_key_enabled: Option<bool>,
+    ) -> Self {
+        Self {
+            apply_server_side_encryption_by_default: Some(apply_server_side_encryption_by_default),
+            bucket_key_enabled,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApplyServerSideEncryptionByDefault {
+    /// `AES256`, `aws:kms` or `aws:kms:dsse`
+    #[serde(rename = "SSEAlgorithm")]
+    pub sse_algorithm: String,
+    #[serde(rename = "KMSMasterKeyID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kms_master_key_id: Option<String>,
+}
+
+impl ApplyServerSideEncryptionByDefault {
+    pub fn new(sse_algorithm: impl Into<String>, kms_master_key_id: Option<String>) -> Self {
+        Self {
+            sse_algorithm: sse_algorithm.into(),
+            kms_master_key_id,
+        }
+    }
+}
+
+/// Transfer Acceleration state of a bucket, as sent to and returned by the `?accelerate`
+/// subresource. `status` is `None` for buckets that never had acceleration configured.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "AccelerateConfiguration")]
+pub struct AccelerateConfiguration {
+    #[serde(rename = "Status")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<AccelerateStatus>,
+}
+
+impl AccelerateConfiguration {
+    pub fn new(status: AccelerateStatus) -> Self {
+        AccelerateConfiguration {
+            status: Some(status),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status == Some(AccelerateStatus::Enabled)
+    }
+}
+
+impl fmt::Display for AccelerateConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+xml_text_enum! {
+    pub enum AccelerateStatus {
+        Enabled => "Enabled",
+        Suspended => "Suspended",
+    }
+}
+
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+fn xsi_namespace() -> String {
+    XSI_NAMESPACE.to_string()
+}
+
+/// Access control list of a bucket or object, as sent to and returned by the `?acl`
+/// subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "AccessControlPolicy")]
+pub struct AccessControlPolicy {
+    #[serde(rename = "Owner")]
+    pub owner: Owner,
+    #[serde(rename = "AccessControlList")]
+    pub access_control_list: AccessControlList,
+}
+
+impl AccessControlPolicy {
+    pub fn new(owner: Owner, grants: Vec<Grant>) -> Self {
+        AccessControlPolicy {
+            owner,
+            access_control_list: AccessControlList { grants },
+        }
+    }
+
+    pub fn grants(&self) -> &[Grant] {
+        &self.access_control_list.grants
+    }
+}
+
+impl fmt::Display for AccessControlPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessControlList {
+    #[serde(rename = "Grant", default)]
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    #[serde(rename = "Grantee")]
+    pub grantee: Grantee,
+    /// `FULL_CONTROL`, `WRITE`, `WRITE_ACP`, `READ` or `READ_ACP`
+    #[serde(rename = "Permission")]
+    pub permission: String,
+}
+
+impl Grant {
+    pub fn new(grantee: Grantee, permission: impl Into<String>) -> Self {
+        Grant {
+            grantee,
+            permission: permission.into(),
+        }
+    }
+}
+
+/// Who a [`Grant`] applies to, identified by canonical user ID, group URI or email address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grantee {
+    #[serde(rename = "@xmlns:xsi", default = "xsi_namespace")]
+    xmlns_xsi: String,
+    /// `CanonicalUser`, `Group` or `AmazonCustomerByEmail`
+    #[serde(rename = "@xsi:type", alias = "@type")]
+    pub grantee_type: String,
+    #[serde(rename = "ID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "DisplayName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(rename = "URI")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(rename = "EmailAddress")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_address: Option<String>,
+}
+
+impl Grantee {
+    fn new(grantee_type: &str) -> Self {
+        Grantee {
+            xmlns_xsi: xsi_namespace(),
+            grantee_type: grantee_type.to_string(),
+            id: None,
+            display_name: None,
+            uri: None,
+            email_address: None,
+        }
+    }
+
+    pub fn canonical_user(id: impl Into<String>) -> Self {
+        Grantee {
+            id: Some(id.into()),
+            ..Grantee::new("CanonicalUser")
+        }
+    }
+
+    /// e.g. `http://acs.amazonaws.com/groups/global/AllUsers`
+    pub fn group(uri: impl Into<String>) -> Self {
+        Grantee {
+            uri: Some(uri.into()),
+            ..Grantee::new("Group")
+        }
+    }
+
+    pub fn email(email_address: impl Into<String>) -> Self {
+        Grantee {
+            email_address: Some(email_address.into()),
+            ..Grantee::new("AmazonCustomerByEmail")
+        }
+    }
+}
+
+/// Object ownership setting of a bucket, as sent to and returned by the `?ownershipControls`
+/// subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "OwnershipControls")]
+pub struct OwnershipControls {
+    #[serde(rename = "Rule")]
+    pub rules: Vec<OwnershipControlsRule>,
+}
+
+impl OwnershipControls {
+    pub fn new(object_ownership: ObjectOwnership) -> Self {
+        OwnershipControls {
+            rules: vec![OwnershipControlsRule { object_ownership }],
+        }
+    }
+
+    /// The object ownership of the first rule; S3 only ever sets one.
+    pub fn object_ownership(&self) -> Option<ObjectOwnership> {
+        self.rules.first().map(|rule| rule.object_ownership)
+    }
+}
+
+impl fmt::Display for OwnershipControls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipControlsRule {
+    #[serde(rename = "ObjectOwnership")]
+    pub object_ownership: ObjectOwnership,
+}
+
+xml_text_enum! {
+    pub enum ObjectOwnership {
+        /// ACLs are disabled, the bucket owner owns every object
+        BucketOwnerEnforced => "BucketOwnerEnforced",
+        /// The bucket owner owns objects uploaded with the `bucket-owner-full-control` canned ACL
+        BucketOwnerPreferred => "BucketOwnerPreferred",
+        /// The uploading account owns the object
+        ObjectWriter => "ObjectWriter",
+    }
+}
+
+/// Configuration of a daily or weekly inventory report listing the objects of a bucket, as
+/// sent to and returned by the `?inventory&id=` subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "InventoryConfiguration")]
+pub struct InventoryConfiguration {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "IsEnabled")]
+    pub is_enabled: bool,
+    #[serde(rename = "Destination")]
+    pub destination: InventoryDestination,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<InventoryFilter>,
+    #[serde(rename = "IncludedObjectVersions")]
+    pub included_object_versions: InventoryIncludedObjectVersions,
+    #[serde(rename = "OptionalFields")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optional_fields: Option<InventoryOptionalFields>,
+    #[serde(rename = "Schedule")]
+    pub schedule: InventorySchedule,
+}
+
+impl InventoryConfiguration {
+    /// An enabled inventory of the current object versions, delivered to the bucket with ARN
+    /// `destination_bucket_arn`, e.g. `arn:aws:s3:::inventory-reports`.
+    pub fn new(
+        id: &str,
+        destination_bucket_arn: &str,
+        format: InventoryFormat,
+        frequency: InventoryFrequency,
+    ) -> Self {
+        InventoryConfiguration {
+            id: id.to_string(),
+            is_enabled: true,
+            destination: InventoryDestination {
+                s3_bucket_destination: InventoryS3BucketDestination {
+                    account_id: None,
+                    bucket: destination_bucket_arn.to_string(),
+                    format,
+                    prefix: None,
+                    encryption: None,
+                },
+            },
+            filter: None,
+            included_object_versions: InventoryIncludedObjectVersions::Current,
+            optional_fields: None,
+            schedule: InventorySchedule { frequency },
+        }
+    }
+}
+
+impl fmt::Display for InventoryConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryDestination {
+    #[serde(rename = "S3BucketDestination")]
+    pub s3_bucket_destination: InventoryS3BucketDestination,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryS3BucketDestination {
+    /// Account expected to own the destination bucket
+    #[serde(rename = "AccountId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// ARN of the destination bucket
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+    #[serde(rename = "Format")]
+    pub format: InventoryFormat,
+    #[serde(rename = "Prefix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Encryption")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<InventoryEncryption>,
+}
+
+/// Server-side encryption of the inventory reports, either `SSE-S3` or `SSE-KMS`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryEncryption {
+    #[serde(rename = "SSE-S3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sse_s3: Option<SseS3>,
+    #[serde(rename = "SSE-KMS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sse_kms: Option<SseKms>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SseS3 {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SseKms {
+    #[serde(rename = "KeyId")]
+    pub key_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryFilter {
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+}
+
+/// Extra fields listed in the inventory report, e.g. `Size`, `LastModified`, `StorageClass`
+/// or `ETag`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryOptionalFields {
+    #[serde(rename = "Field", default)]
+    pub fields: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventorySchedule {
+    #[serde(rename = "Frequency")]
+    pub frequency: InventoryFrequency,
+}
+
+xml_text_enum! {
+    pub enum InventoryFormat {
+        Csv => "CSV",
+        Orc => "ORC",
+        Parquet => "Parquet",
+    }
+}
+
+xml_text_enum! {
+    pub enum InventoryFrequency {
+        Daily => "Daily",
+        Weekly => "Weekly",
+    }
+}
+
+xml_text_enum! {
+    pub enum InventoryIncludedObjectVersions {
+        All => "All",
+        Current => "Current",
+    }
+}
+
+/// A page of the inventory configurations of a bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ListInventoryConfigurationsResult {
+    #[serde(rename = "InventoryConfiguration", default)]
+    pub inventory_configurations: Vec<InventoryConfiguration>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "ContinuationToken")]
+    pub continuation_token: Option<String>,
+    /// Pass to the next list call to get the next page, if `is_truncated`
+    #[serde(rename = "NextContinuationToken")]
+    pub next_continuation_token: Option<String>,
+}
+
+/// Configuration of storage class analysis, as sent to and returned by the `?analytics&id=`
+/// subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "AnalyticsConfiguration")]
+pub struct AnalyticsConfiguration {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ConfigurationFilter>,
+    #[serde(rename = "StorageClassAnalysis")]
+    pub storage_class_analysis: StorageClassAnalysis,
+}
+
+impl AnalyticsConfiguration {
+    /// Analyze the objects matched by `filter`, the whole bucket if `None`, without exporting
+    /// the results.
+    pub fn new(id: &str, filter: Option<ConfigurationFilter>) -> Self {
+        AnalyticsConfiguration {
+            id: id.to_string(),
+            filter,
+            storage_class_analysis: StorageClassAnalysis { data_export: None },
+        }
+    }
+}
+
+impl fmt::Display for AnalyticsConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageClassAnalysis {
+    #[serde(rename = "DataExport")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_export: Option<StorageClassAnalysisDataExport>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageClassAnalysisDataExport {
+    /// Only `V_1` is supported
+    #[serde(rename = "OutputSchemaVersion")]
+    pub output_schema_version: String,
+    #[serde(rename = "Destination")]
+    pub destination: AnalyticsExportDestination,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsExportDestination {
+    #[serde(rename = "S3BucketDestination")]
+    pub s3_bucket_destination: AnalyticsS3BucketDestination,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsS3BucketDestination {
+    /// Only `CSV` is supported
+    #[serde(rename = "Format")]
+    pub format: String,
+    #[serde(rename = "BucketAccountId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket_account_id: Option<String>,
+    /// ARN of the destination bucket
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+    #[serde(rename = "Prefix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+/// A page of the analytics configurations of a bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ListAnalyticsConfigurationsResult {
+    #[serde(rename = "AnalyticsConfiguration", default)]
+    pub analytics_configurations: Vec<AnalyticsConfiguration>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "ContinuationToken")]
+    pub continuation_token: Option<String>,
+    /// Pass to the next list call to get the next page, if `is_truncated`
+    #[serde(rename = "NextContinuationToken")]
+    pub next_continuation_token: Option<String>,
+}
+
+/// Configuration of request metrics published to CloudWatch, as sent to and returned by the
+/// `?metrics&id=` subresource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "MetricsConfiguration")]
+pub struct MetricsConfiguration {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ConfigurationFilter>,
+}
+
+impl MetricsConfiguration {
+    /// Metrics for the objects matched by `filter`, the whole bucket if `None`.
+    pub fn new(id: &str, filter: Option<ConfigurationFilter>) -> Self {
+        MetricsConfiguration {
+            id: id.to_string(),
+            filter,
+        }
+    }
+}
+
+impl fmt::Display for MetricsConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+/// A page of the metrics configurations of a bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ListMetricsConfigurationsResult {
+    #[serde(rename = "MetricsConfiguration", default)]
+    pub metrics_configurations: Vec<MetricsConfiguration>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "ContinuationToken")]
+    pub continuation_token: Option<String>,
+    /// Pass to the next list call to get the next page, if `is_truncated`
+    #[serde(rename = "NextContinuationToken")]
+    pub next_continuation_token: Option<String>,
+}
+
+/// Objects an analytics or metrics configuration applies to. Set one of the fields, combine
+/// conditions with `and`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigurationFilter {
+    #[serde(rename = "Prefix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Tag")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<FilterTag>,
+    /// Metrics configurations only
+    #[serde(rename = "AccessPointArn")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_point_arn: Option<String>,
+    #[serde(rename = "And")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub and: Option<ConfigurationFilterAnd>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigurationFilterAnd {
+    #[serde(rename = "Prefix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Tag", default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<FilterTag>,
+    #[serde(rename = "AccessPointArn")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_point_arn: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterTag {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+/// Destinations S3 publishes bucket events to, as sent to and returned by the `?notification`
+/// subresource. Putting an empty configuration turns notifications off.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "NotificationConfiguration")]
+pub struct NotificationConfiguration {
+    #[serde(rename = "TopicConfiguration", default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topic_configurations: Vec<TopicConfiguration>,
+    #[serde(rename = "QueueConfiguration", default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub queue_configurations: Vec<QueueConfiguration>,
+    #[serde(rename = "CloudFunctionConfiguration", default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lambda_function_configurations: Vec<LambdaFunctionConfiguration>,
+}
+
+impl fmt::Display for NotificationConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+/// Publish events to an SNS topic.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicConfiguration {
+    #[serde(rename = "Id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "Topic")]
+    pub topic_arn: String,
+    /// Event types, e.g. `s3:ObjectCreated:*`
+    #[serde(rename = "Event")]
+    pub events: Vec<String>,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<NotificationFilter>,
+}
+
+/// Send events to an SQS queue.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueConfiguration {
+    #[serde(rename = "Id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "Queue")]
+    pub queue_arn: String,
+    /// Event types, e.g. `s3:ObjectCreated:*`
+    #[serde(rename = "Event")]
+    pub events: Vec<String>,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<NotificationFilter>,
+}
+
+/// Invoke a Lambda function with events.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LambdaFunctionConfiguration {
+    #[serde(rename = "Id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "CloudFunction")]
+    pub lambda_function_arn: String,
+    /// Event types, e.g. `s3:ObjectCreated:*`
+    #[serde(rename = "Event")]
+    pub events: Vec<String>,
+    #[serde(rename = "Filter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<NotificationFilter>,
+}
+
+/// Restricts notifications to object keys with a given prefix and/or suffix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationFilter {
+    #[serde(rename = "S3Key")]
+    pub key: NotificationKeyFilter,
+}
+
+impl NotificationFilter {
+    pub fn new(prefix: Option<&str>, suffix: Option<&str>) -> Self {
+        let rules = [("prefix", prefix), ("suffix", suffix)]
+            .iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| FilterRule {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .collect();
+        NotificationFilter {
+            key: NotificationKeyFilter { rules },
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.rule("prefix")
+    }
+
+    pub fn suffix(&self) -> Option<&str> {
+        self.rule("suffix")
+    }
+
+    fn rule(&self, name: &str) -> Option<&str> {
+        self.key
+            .rules
+            .iter()
+            .find(|rule| rule.name.eq_ignore_ascii_case(name))
+            .map(|rule| rule.value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationKeyFilter {
+    #[serde(rename = "FilterRule", default)]
+    pub rules: Vec<FilterRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterRule {
+    /// `prefix` or `suffix`
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+/// An object to delete in a batch with [`Bucket::delete_objects_versioned`], the current
+/// version unless `version_id` is given.
+///
+/// [`Bucket::delete_objects_versioned`]: crate::Bucket::delete_objects_versioned
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ObjectIdentifier {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+}
+
+impl ObjectIdentifier {
+    pub fn new(key: impl Into<String>) -> Self {
+        ObjectIdentifier {
+            key: key.into(),
+            version_id: None,
+        }
+    }
+
+    pub fn with_version(key: impl Into<String>, version_id: impl Into<String>) -> Self {
+        ObjectIdentifier {
+            key: key.into(),
+            version_id: Some(version_id.into()),
+        }
+    }
+}
+
+/// Body of a DeleteObjects request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename = "Delete")]
+pub struct DeleteObjectsRequest {
+    /// Only report the objects that failed to be deleted
+    #[serde(rename = "Quiet")]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub quiet: bool,
+    #[serde(rename = "Object")]
+    pub objects: Vec<ObjectIdentifier>,
+}
+
+impl fmt::Display for DeleteObjectsRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xml = quick_xml::se::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", xml)
+    }
+}
+
+/// Outcome of a DeleteObjects request. Unless the request was quiet, `deleted` lists every
+/// object deleted, `errors` those that couldn't be.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(from = "DeleteResult")]
+pub struct DeleteObjectsResult {
+    pub deleted: Vec<DeletedObject>,
+    pub errors: Vec<DeleteObjectError>,
+}
+
+/// The `DeleteResult` as S3 sends it, with the `Deleted` and `Error` elements interleaved in
+/// the order the keys were processed, which a struct with a list of each can't be read from.
+#[derive(Deserialize)]
+struct DeleteResult {
+    #[serde(rename = "$value", default)]
+    entries: Vec<DeleteResultEntry>,
+}
+
+#[derive(Deserialize)]
+enum DeleteResultEntry {
+    Deleted(DeletedObject),
+    Error(DeleteObjectError),
+    #[serde(other)]
+    Other,
+}
+
+impl From<DeleteResult> for DeleteObjectsResult {
+    fn from(result: DeleteResult) -> Self {
+        let mut delete_objects_result = DeleteObjectsResult::default();
+        for entry in result.entries {
+            match entry {
+                DeleteResultEntry::Deleted(deleted) => delete_objects_result.deleted.push(deleted),
+                DeleteResultEntry::Error(error) => delete_objects_result.errors.push(error),
+                DeleteResultEntry::Other => {}
+            }
+        }
+        delete_objects_result
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DeletedObject {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: Option<String>,
+    /// Whether a delete marker was created, or deleted when deleting a delete marker by version
+    #[serde(rename = "DeleteMarker", default)]
+    pub delete_marker: bool,
+    #[serde(rename = "DeleteMarkerVersionId")]
+    pub delete_marker_version_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DeleteObjectError {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: Option<String>,
+    /// Error code, e.g. `AccessDenied`
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Message")]
+    pub message: Option<String>,
+}
+
 #[cfg(test)]
 mod test {
-    use super::{CorsConfiguration, CorsRule};
+    use super::{
+        AccelerateConfiguration, AccelerateStatus, AccessControlPolicy, ConfigurationFilter,
+        ConfigurationFilterAnd, CorsConfiguration, CorsRule, DeleteObjectsRequest,
+        DeleteObjectsResult, FilterTag, Grant, Grantee, InventoryConfiguration, InventoryFormat,
+        InventoryFrequency, InventoryIncludedObjectVersions, ListBucketResult,
+        ListInventoryConfigurationsResult, ListMultipartUploadsResult, MetricsConfiguration,
+        NotificationConfiguration, NotificationFilter, Object, ObjectIdentifier, ObjectOwnership,
+        Owner, OwnershipControls, ServerSideEncryptionConfiguration, TopicConfiguration,
+    };
 
     #[test]
     fn cors_config_serde() {
@@ -400,4 +1491,524 @@ mod test {
             r#"<CORSConfiguration><CORSRule><AllowedHeader>Authorization</AllowedHeader><AllowedHeader>Header2</AllowedHeader><AllowedMethod>GET</AllowedMethod><AllowedMethod>DELETE</AllowedMethod><AllowedOrigin>*</AllowedOrigin><ID>lala</ID></CORSRule><CORSRule><AllowedHeader>Authorization</AllowedHeader><AllowedHeader>Header2</AllowedHeader><AllowedMethod>GET</AllowedMethod><AllowedMethod>DELETE</AllowedMethod><AllowedOrigin>*</AllowedOrigin><ID>lala</ID></CORSRule></CORSConfiguration>"#
         )
     }
+
+    #[test]
+    fn delete_objects_serde() {
+        let request = DeleteObjectsRequest {
+            quiet: true,
+            objects: vec![
+                ObjectIdentifier::new("current.file"),
+                ObjectIdentifier::with_version("versioned.file", "3HL4kqtJlcpXroDTDmjVBH40Nrjfkd"),
+            ],
+        };
+        assert_eq!(
+            request.to_string(),
+            "<Delete><Quiet>true</Quiet><Object><Key>current.file</Key></Object><Object><Key>versioned.file</Key><VersionId>3HL4kqtJlcpXroDTDmjVBH40Nrjfkd</VersionId></Object></Delete>"
+        );
+
+        let request = DeleteObjectsRequest {
+            quiet: false,
+            objects: vec![ObjectIdentifier::new("current.file")],
+        };
+        assert_eq!(
+            request.to_string(),
+            "<Delete><Object><Key>current.file</Key></Object></Delete>"
+        );
+
+        let result = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Deleted>
+                    <Key>current.file</Key>
+                    <DeleteMarker>true</DeleteMarker>
+                    <DeleteMarkerVersionId>NeQt5xeFTfgPJD8B4CGWnkSLtluMr11s</DeleteMarkerVersionId>
+                </Deleted>
+                <Error>
+                    <Key>versioned.file</Key>
+                    <VersionId>3HL4kqtJlcpXroDTDmjVBH40Nrjfkd</VersionId>
+                    <Code>AccessDenied</Code>
+                    <Message>Access Denied</Message>
+                </Error>
+            </DeleteResult>
+        "#;
+        let result = quick_xml::de::from_str::<DeleteObjectsResult>(result).unwrap();
+        assert_eq!(result.deleted.len(), 1);
+        assert!(result.deleted[0].delete_marker);
+        assert_eq!(result.errors[0].key, "versioned.file");
+        assert_eq!(result.errors[0].code, "AccessDenied");
+    }
+
+    #[test]
+    fn object_typed_values() {
+        let contents = r#"
+            <Contents>
+                <Key>photos/2006/February/sample.jpg</Key>
+                <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                <ETag>"bf1d737a4d46a19f3bced6905cc8b902"</ETag>
+                <Size>5368709120</Size>
+                <StorageClass>STANDARD</StorageClass>
+            </Contents>
+        "#;
+
+        let object = quick_xml::de::from_str::<Object>(contents).unwrap();
+        assert_eq!(object.size, 5_368_709_120);
+        assert_eq!(
+            object.last_modified_datetime().unwrap(),
+            time::macros::datetime!(2023-06-04 20:13:37.837 UTC)
+        );
+
+        let malformed = Object {
+            last_modified: "Sun, 04 Jun 2023 20:13:37 GMT".to_string(),
+            ..object
+        };
+        assert!(malformed.last_modified_datetime().is_err());
+    }
+
+    #[test]
+    fn object_checksum_algorithm_and_restore_status() {
+        let contents = r#"
+            <Contents>
+                <Key>archive/2019.tar</Key>
+                <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                <ETag>"bf1d737a4d46a19f3bced6905cc8b902"</ETag>
+                <ChecksumAlgorithm>SHA256</ChecksumAlgorithm>
+                <Size>1024</Size>
+                <Owner>
+                    <ID>75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a</ID>
+                    <DisplayName>mtd</DisplayName>
+                </Owner>
+                <StorageClass>GLACIER</StorageClass>
+                <RestoreStatus>
+                    <IsRestoreInProgress>false</IsRestoreInProgress>
+                    <RestoreExpiryDate>2023-07-01T00:00:00.000Z</RestoreExpiryDate>
+                </RestoreStatus>
+            </Contents>
+        "#;
+
+        let object = quick_xml::de::from_str::<Object>(contents).unwrap();
+        assert_eq!(object.checksum_algorithm, vec!["SHA256".to_string()]);
+        let restore_status = object.restore_status.unwrap();
+        assert!(!restore_status.is_restore_in_progress);
+        assert_eq!(
+            restore_status.restore_expiry_date.as_deref(),
+            Some("2023-07-01T00:00:00.000Z")
+        );
+        assert_eq!(object.owner.unwrap().display_name.as_deref(), Some("mtd"));
+
+        // Both are optional
+        let contents = r#"
+            <Contents>
+                <Key>plain.txt</Key>
+                <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                <Size>4</Size>
+            </Contents>
+        "#;
+        let object = quick_xml::de::from_str::<Object>(contents).unwrap();
+        assert!(object.checksum_algorithm.is_empty());
+        assert!(object.restore_status.is_none());
+    }
+
+    #[test]
+    fn list_multipart_uploads_result_markers() {
+        let response = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <ListMultipartUploadsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Bucket>rust-s3-test</Bucket>
+                <KeyMarker>my-movie.m2ts</KeyMarker>
+                <UploadIdMarker>YW55IGlkZWEgd2h5IGVsdmluZydzIHVwbG9hZCBmYWlsZWQ</UploadIdMarker>
+                <NextKeyMarker>my-movie.m2ts</NextKeyMarker>
+                <NextUploadIdMarker>VXBsb2FkIElEIGZvciBlbHZpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</NextUploadIdMarker>
+                <MaxUploads>1</MaxUploads>
+                <IsTruncated>true</IsTruncated>
+                <Upload>
+                    <Key>my-movie.m2ts</Key>
+                    <UploadId>VXBsb2FkIElEIGZvciBlbHZpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId>
+                    <StorageClass>STANDARD</StorageClass>
+                    <Initiated>2010-11-10T20:48:33.000Z</Initiated>
+                </Upload>
+            </ListMultipartUploadsResult>
+        "#;
+
+        let parsed = quick_xml::de::from_str::<ListMultipartUploadsResult>(response).unwrap();
+
+        assert!(parsed.is_truncated);
+        assert_eq!(parsed.marker.as_deref(), Some("my-movie.m2ts"));
+        assert_eq!(
+            parsed.upload_id_marker.as_deref(),
+            Some("YW55IGlkZWEgd2h5IGVsdmluZydzIHVwbG9hZCBmYWlsZWQ")
+        );
+        assert_eq!(parsed.next_marker.as_deref(), Some("my-movie.m2ts"));
+        assert_eq!(
+            parsed.next_upload_id_marker.as_deref(),
+            Some("VXBsb2FkIElEIGZvciBlbHZpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA")
+        );
+        assert_eq!(parsed.uploads.len(), 1);
+    }
+
+    #[test]
+    fn list_bucket_result_flatten() {
+        let first_page = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Name>rust-s3-test</Name>
+                <Prefix>photos/</Prefix>
+                <Delimiter>/</Delimiter>
+                <IsTruncated>true</IsTruncated>
+                <NextContinuationToken>token</NextContinuationToken>
+                <Contents>
+                    <Key>photos/a.jpg</Key>
+                    <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                    <Size>10</Size>
+                </Contents>
+                <Contents>
+                    <Key>photos/b.jpg</Key>
+                    <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                    <Size>20</Size>
+                </Contents>
+                <CommonPrefixes><Prefix>photos/2022/</Prefix></CommonPrefixes>
+                <CommonPrefixes><Prefix>photos/2023/</Prefix></CommonPrefixes>
+            </ListBucketResult>
+        "#;
+        let second_page = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Name>rust-s3-test</Name>
+                <Prefix>photos/</Prefix>
+                <Delimiter>/</Delimiter>
+                <IsTruncated>false</IsTruncated>
+                <Contents>
+                    <Key>photos/b.jpg</Key>
+                    <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                    <Size>20</Size>
+                </Contents>
+                <Contents>
+                    <Key>photos/c.jpg</Key>
+                    <LastModified>2023-06-04T20:13:37.837Z</LastModified>
+                    <Size>30</Size>
+                </Contents>
+                <CommonPrefixes><Prefix>photos/2023/</Prefix></CommonPrefixes>
+                <CommonPrefixes><Prefix>photos/2024/</Prefix></CommonPrefixes>
+            </ListBucketResult>
+        "#;
+
+        let results = [first_page, second_page]
+            .iter()
+            .map(|page| quick_xml::de::from_str::<ListBucketResult>(page).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(results[0].objects().count(), 2);
+        assert_eq!(
+            results[0].prefixes().collect::<Vec<_>>(),
+            vec!["photos/2022/", "photos/2023/"]
+        );
+
+        let entries = ListBucketResult::flatten(&results);
+        assert_eq!(
+            entries.keys,
+            vec!["photos/a.jpg", "photos/b.jpg", "photos/c.jpg"]
+        );
+        assert_eq!(
+            entries.common_prefixes,
+            vec!["photos/2022/", "photos/2023/", "photos/2024/"]
+        );
+    }
+
+    #[test]
+    fn server_side_encryption_configuration_aes256_roundtrip() {
+        let config = ServerSideEncryptionConfiguration::aes256();
+
+        let se = config.to_string();
+        assert_eq!(
+            se,
+            "<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault><SSEAlgorithm>AES256</SSEAlgorithm></ApplyServerSideEncryptionByDefault></Rule></ServerSideEncryptionConfiguration>"
+        );
+
+        let de: ServerSideEncryptionConfiguration = quick_xml::de::from_str(&se).unwrap();
+        assert_eq!(de, config);
+    }
+
+    #[test]
+    fn server_side_encryption_configuration_kms_roundtrip() {
+        let config = ServerSideEncryptionConfiguration::kms(
+            Some("arn:aws:kms:us-east-1:1234/5678example".to_string()),
+            true,
+        );
+
+        let se = config.to_string();
+        assert_eq!(
+            se,
+            "<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault><SSEAlgorithm>aws:kms</SSEAlgorithm><KMSMasterKeyID>arn:aws:kms:us-east-1:1234/5678example</KMSMasterKeyID></ApplyServerSideEncryptionByDefault><BucketKeyEnabled>true</BucketKeyEnabled></Rule></ServerSideEncryptionConfiguration>"
+        );
+        assert_eq!(
+            quick_xml::de::from_str::<ServerSideEncryptionConfiguration>(&se).unwrap(),
+            config
+        );
+
+        // As returned by GetBucketEncryption
+        let response = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ServerSideEncryptionConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Rule>
+                    <ApplyServerSideEncryptionByDefault>
+                        <SSEAlgorithm>aws:kms</SSEAlgorithm>
+                        <KMSMasterKeyID>arn:aws:kms:us-east-1:1234/5678example</KMSMasterKeyID>
+                    </ApplyServerSideEncryptionByDefault>
+                    <BucketKeyEnabled>true</BucketKeyEnabled>
+                </Rule>
+            </ServerSideEncryptionConfiguration>"#;
+        let de: ServerSideEncryptionConfiguration = quick_xml::de::from_str(response).unwrap();
+        assert_eq!(de, config);
+    }
+
+    #[test]
+    fn accelerate_configuration_serde() {
+        let config = AccelerateConfiguration::new(AccelerateStatus::Enabled);
+        let se = config.to_string();
+        assert_eq!(
+            se,
+            "<AccelerateConfiguration><Status>Enabled</Status></AccelerateConfiguration>"
+        );
+        assert_eq!(
+            quick_xml::de::from_str::<AccelerateConfiguration>(&se).unwrap(),
+            config
+        );
+
+        let never_configured = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <AccelerateConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"/>"#;
+        let de: AccelerateConfiguration = quick_xml::de::from_str(never_configured).unwrap();
+        assert!(!de.is_enabled());
+        assert_eq!(de.status, None);
+    }
+
+    #[test]
+    fn ownership_controls_roundtrip() {
+        for object_ownership in [
+            ObjectOwnership::BucketOwnerEnforced,
+            ObjectOwnership::BucketOwnerPreferred,
+            ObjectOwnership::ObjectWriter,
+        ] {
+            let controls = OwnershipControls::new(object_ownership);
+            let se = controls.to_string();
+            assert_eq!(
+                se,
+                format!(
+                    "<OwnershipControls><Rule><ObjectOwnership>{}</ObjectOwnership></Rule></OwnershipControls>",
+                    object_ownership.as_str()
+                )
+            );
+            let de: OwnershipControls = quick_xml::de::from_str(&se).unwrap();
+            assert_eq!(de.object_ownership(), Some(object_ownership));
+        }
+    }
+
+    #[test]
+    fn access_control_policy_roundtrip() {
+        let response = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <AccessControlPolicy xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Owner>
+                    <ID>75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a</ID>
+                    <DisplayName>CustomersName@amazon.com</DisplayName>
+                </Owner>
+                <AccessControlList>
+                    <Grant>
+                        <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="CanonicalUser">
+                            <ID>75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a</ID>
+                            <DisplayName>CustomersName@amazon.com</DisplayName>
+                        </Grantee>
+                        <Permission>FULL_CONTROL</Permission>
+                    </Grant>
+                    <Grant>
+                        <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="Group">
+                            <URI>http://acs.amazonaws.com/groups/global/AllUsers</URI>
+                        </Grantee>
+                        <Permission>READ</Permission>
+                    </Grant>
+                </AccessControlList>
+            </AccessControlPolicy>"#;
+        let policy: AccessControlPolicy = quick_xml::de::from_str(response).unwrap();
+
+        let owner_id = "75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a";
+        let mut owner = Grantee::canonical_user(owner_id);
+        owner.display_name = Some("CustomersName@amazon.com".to_string());
+        let expected = AccessControlPolicy::new(
+            Owner {
+                display_name: Some("CustomersName@amazon.com".to_string()),
+                id: owner_id.to_string(),
+            },
+            vec![
+                Grant::new(owner, "FULL_CONTROL"),
+                Grant::new(
+                    Grantee::group("http://acs.amazonaws.com/groups/global/AllUsers"),
+                    "READ",
+                ),
+            ],
+        );
+        assert_eq!(policy, expected);
+
+        let se = policy.to_string();
+        assert!(se.contains(r#"<Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="Group"><URI>"#));
+        assert_eq!(
+            quick_xml::de::from_str::<AccessControlPolicy>(&se).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn inventory_configuration_roundtrip() {
+        let response = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <InventoryConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Id>report1</Id>
+                <IsEnabled>true</IsEnabled>
+                <Destination>
+                    <S3BucketDestination>
+                        <Format>CSV</Format>
+                        <AccountId>123456789012</AccountId>
+                        <Bucket>arn:aws:s3:::destination-bucket</Bucket>
+                        <Prefix>prefix1</Prefix>
+                        <Encryption>
+                            <SSE-S3></SSE-S3>
+                        </Encryption>
+                    </S3BucketDestination>
+                </Destination>
+                <Schedule>
+                    <Frequency>Daily</Frequency>
+                </Schedule>
+                <Filter>
+                    <Prefix>filterPrefix/</Prefix>
+                </Filter>
+                <IncludedObjectVersions>All</IncludedObjectVersions>
+                <OptionalFields>
+                    <Field>Size</Field>
+                    <Field>LastModified</Field>
+                    <Field>ETag</Field>
+                </OptionalFields>
+            </InventoryConfiguration>
+        "#;
+
+        let parsed = quick_xml::de::from_str::<InventoryConfiguration>(response).unwrap();
+        assert_eq!(parsed.id, "report1");
+        assert!(parsed.is_enabled);
+        let destination = &parsed.destination.s3_bucket_destination;
+        assert_eq!(destination.format, InventoryFormat::Csv);
+        assert_eq!(destination.bucket, "arn:aws:s3:::destination-bucket");
+        assert!(destination.encryption.as_ref().unwrap().sse_s3.is_some());
+        assert_eq!(parsed.schedule.frequency, InventoryFrequency::Daily);
+        assert_eq!(
+            parsed.included_object_versions,
+            InventoryIncludedObjectVersions::All
+        );
+        assert_eq!(
+            parsed.optional_fields.as_ref().unwrap().fields,
+            vec!["Size", "LastModified", "ETag"]
+        );
+
+        let reparsed =
+            quick_xml::de::from_str::<InventoryConfiguration>(&parsed.to_string()).unwrap();
+        assert_eq!(reparsed, parsed);
+
+        let configuration = InventoryConfiguration::new(
+            "weekly",
+            "arn:aws:s3:::destination-bucket",
+            InventoryFormat::Parquet,
+            InventoryFrequency::Weekly,
+        );
+        assert_eq!(
+            configuration.to_string(),
+            "<InventoryConfiguration><Id>weekly</Id><IsEnabled>true</IsEnabled><Destination><S3BucketDestination><Bucket>arn:aws:s3:::destination-bucket</Bucket><Format>Parquet</Format></S3BucketDestination></Destination><IncludedObjectVersions>Current</IncludedObjectVersions><Schedule><Frequency>Weekly</Frequency></Schedule></InventoryConfiguration>"
+        );
+    }
+
+    #[test]
+    fn list_inventory_configurations_result() {
+        let response = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <ListInventoryConfigurationsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <InventoryConfiguration>
+                    <Id>report1</Id>
+                    <IsEnabled>true</IsEnabled>
+                    <Destination>
+                        <S3BucketDestination>
+                            <Format>ORC</Format>
+                            <Bucket>arn:aws:s3:::destination-bucket</Bucket>
+                        </S3BucketDestination>
+                    </Destination>
+                    <Schedule><Frequency>Weekly</Frequency></Schedule>
+                    <IncludedObjectVersions>Current</IncludedObjectVersions>
+                </InventoryConfiguration>
+                <IsTruncated>true</IsTruncated>
+                <NextContinuationToken>token</NextContinuationToken>
+            </ListInventoryConfigurationsResult>
+        "#;
+
+        let parsed =
+            quick_xml::de::from_str::<ListInventoryConfigurationsResult>(response).unwrap();
+        assert_eq!(parsed.inventory_configurations.len(), 1);
+        assert_eq!(
+            parsed.inventory_configurations[0]
+                .destination
+                .s3_bucket_destination
+                .format,
+            InventoryFormat::Orc
+        );
+        assert!(parsed.is_truncated);
+        assert_eq!(parsed.next_continuation_token.as_deref(), Some("token"));
+    }
+
+    #[test]
+    fn metrics_configuration_roundtrip() {
+        let configuration = MetricsConfiguration::new(
+            "documents",
+            Some(ConfigurationFilter {
+                and: Some(ConfigurationFilterAnd {
+                    prefix: Some("documents/".to_string()),
+                    tags: vec![FilterTag {
+                        key: "priority".to_string(),
+                        value: "high".to_string(),
+                    }],
+                    access_point_arn: None,
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let xml = configuration.to_string();
+        assert_eq!(
+            xml,
+            "<MetricsConfiguration><Id>documents</Id><Filter><And><Prefix>documents/</Prefix><Tag><Key>priority</Key><Value>high</Value></Tag></And></Filter></MetricsConfiguration>"
+        );
+        let parsed = quick_xml::de::from_str::<MetricsConfiguration>(&xml).unwrap();
+        assert_eq!(parsed, configuration);
+    }
+
+    #[test]
+    fn notification_configuration_roundtrip() {
+        let configuration = NotificationConfiguration {
+            topic_configurations: vec![TopicConfiguration {
+                id: Some("new-images".to_string()),
+                topic_arn: "arn:aws:sns:us-east-1:123456789012:images".to_string(),
+                events: vec!["s3:ObjectCreated:*".to_string()],
+                filter: Some(NotificationFilter::new(Some("images/"), None)),
+            }],
+            ..Default::default()
+        };
+
+        let xml = configuration.to_string();
+        assert_eq!(
+            xml,
+            "<NotificationConfiguration><TopicConfiguration><Id>new-images</Id><Topic>arn:aws:sns:us-east-1:123456789012:images</Topic><Event>s3:ObjectCreated:*</Event><Filter><S3Key><FilterRule><Name>prefix</Name><Value>images/</Value></FilterRule></S3Key></Filter></TopicConfiguration></NotificationConfiguration>"
+        );
+        let parsed = quick_xml::de::from_str::<NotificationConfiguration>(&xml).unwrap();
+        assert_eq!(parsed, configuration);
+        let filter = parsed.topic_configurations[0].filter.as_ref().unwrap();
+        assert_eq!(filter.prefix(), Some("images/"));
+        assert_eq!(filter.suffix(), None);
+
+        // Clearing the configuration
+        let cleared = NotificationConfiguration::default();
+        assert_eq!(cleared.to_string(), "<NotificationConfiguration/>");
+        let response = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <NotificationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"/>"#;
+        let parsed = quick_xml::de::from_str::<NotificationConfiguration>(response).unwrap();
+        assert_eq!(parsed, cleared);
+    }
 }
@@ -0,0 +1,503 @@
+//! An in-memory S3 to run whole flows against in tests, without credentials or a network
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use url::Url;
+
+use super::{fake_credentials, http_response, split_request};
+use crate::{Bucket, Region};
+
+const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+const LAST_MODIFIED_ISO: &str = "2015-10-21T07:28:00.000Z";
+
+#[derive(Clone)]
+struct StoredObject {
+    content: Vec<u8>,
+    content_type: String,
+    etag: String,
+    /// `x-amz-meta-*` headers, by lowercase name
+    metadata: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Objects by bucket, then key
+    buckets: HashMap<String, BTreeMap<String, StoredObject>>,
+    /// Parts of the multipart uploads in progress, by upload id, then part number
+    uploads: HashMap<String, (StoredObject, BTreeMap<u32, Vec<u8>>)>,
+    next_upload_id: u64,
+    /// Method and path of each request served, in order
+    requests: Vec<String>,
+}
+
+/// A request as [`MockS3`] sees it.
+struct MockRequest {
+    method: String,
+    bucket: String,
+    key: String,
+    query: HashMap<String, String>,
+    /// Headers, by lowercase name
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// An S3 server keeping objects in memory, serving PutObject, GetObject (ranges included),
+/// HeadObject, DeleteObject, CopyObject, ListObjects v1 and v2, and multipart uploads, for
+/// path style buckets. Requests aren't authenticated, GETs honor `If-Match`.
+///
+/// The server stops once dropped.
+pub struct MockS3 {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl MockS3 {
+    /// Start serving on a local port, each connection on its own thread.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let server = {
+            let state = state.clone();
+            let stopped = stopped.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let state = state.clone();
+                    std::thread::spawn(move || serve_connection(stream.unwrap(), &state));
+                }
+            })
+        };
+
+        Self {
+            address,
+            state,
+            stopped,
+            server: Some(server),
+        }
+    }
+
+    /// A path style bucket called `name` on the server, with fake credentials.
+    pub fn bucket(&self, name: &str) -> Bucket {
+        let region = Region::Custom {
+            region: "custom-region".to_owned(),
+            endpoint: format!("http://{}", self.address),
+        };
+        Bucket::new(name, region, fake_credentials())
+            .unwrap()
+            .with_path_style()
+    }
+
+    /// Content of the object `key` in `bucket`, if there's one.
+    pub fn object(&self, bucket: &str, key: &str) -> Option<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        let object = state.buckets.get(bucket)?.get(key)?;
+        Some(object.content.clone())
+    }
+
+    /// Keys of the objects in `bucket`, in order.
+    pub fn keys(&self, bucket: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .buckets
+            .get(bucket)
+            .map(|objects| objects.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Method and path of each request served so far, e.g. `HEAD /bucket/key`.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for MockS3 {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the accept loop to notice
+        let _ = TcpStream::connect(self.address);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+/// Answer a single request on `stream`, then close it.
+fn serve_connection(mut stream: TcpStream, state: &Mutex<State>) {
+    let mut received = Vec::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let (head, body) = loop {
+        if let Some(request) = split_request(&received) {
+            break request;
+        }
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+        }
+    };
+    let response = match parse(&head, body) {
+        Some(request) => {
+            let mut state = state.lock().unwrap();
+            state.requests.push(format!(
+                "{} /{}/{}",
+                request.method, request.bucket, request.key
+            ));
+            handle(request, &mut state)
+        }
+        None => error("400 Bad Request", "InvalidRequest"),
+    };
+    let _ = stream.write_all(&response);
+}
+
+fn parse(head: &str, body: Vec<u8>) -> Option<MockRequest> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let url = Url::parse(&format!("http://localhost{}", request_line.next()?)).ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let path = percent_encoding::percent_decode_str(url.path())
+        .decode_utf8()
+        .ok()?
+        .into_owned();
+    let (bucket, key) = path[1..].split_once('/').unwrap_or((&path[1..], ""));
+    Some(MockRequest {
+        method,
+        bucket: bucket.to_string(),
+        key: key.to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body,
+    })
+}
+
+fn handle(request: MockRequest, state: &mut State) -> Vec<u8> {
+    let upload_id = request.query.get("uploadId").cloned();
+    match (request.method.as_str(), request.key.is_empty()) {
+        ("GET", true) => list(&request, state),
+        ("PUT", false) => match (upload_id, request.query.get("partNumber")) {
+            (Some(upload_id), Some(part_number)) => {
+                upload_part(&request, state, &upload_id, part_number)
+            }
+            _ => put(request, state),
+        },
+        ("GET", false) | ("HEAD", false) => get(&request, state),
+        ("DELETE", false) => {
+            match upload_id {
+                Some(upload_id) => {
+                    state.uploads.remove(&upload_id);
+                }
+                None => {
+                    if let Some(objects) = state.buckets.get_mut(&request.bucket) {
+                        objects.remove(&request.key);
+                    }
+                }
+            }
+            http_response("204 No Content", &[], "")
+        }
+        ("POST", false) if request.query.contains_key("uploads") => {
+            initiate_multipart_upload(request, state)
+        }
+        ("POST", false) => match upload_id {
+            Some(upload_id) => complete_multipart_upload(&request, state, &upload_id),
+            None => error("400 Bad Request", "InvalidRequest"),
+        },
+        _ => error("501 Not Implemented", "NotImplemented"),
+    }
+}
+
+fn error(status: &str, code: &str) -> Vec<u8> {
+    http_response(
+        status,
+        &[("Content-Type", "application/xml")],
+        format!("<Error><Code>{}</Code></Error>", code),
+    )
+}
+
+fn etag(content: &[u8]) -> String {
+    format!("\"{:x}\"", md5::compute(content))
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn stored_object(request: &MockRequest, content: Vec<u8>) -> StoredObject {
+    StoredObject {
+        etag: etag(&content),
+        content,
+        content_type: request
+            .headers
+            .get("content-type")
+            .cloned()
+            .unwrap_or_else(|| "binary/octet-stream".to_string()),
+        metadata: request
+            .headers
+            .iter()
+            .filter(|(name, _)| name.starts_with("x-amz-meta-"))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    }
+}
+
+fn put(request: MockRequest, state: &mut State) -> Vec<u8> {
+    let object = match request.headers.get("x-amz-copy-source") {
+        Some(source) => {
+            let source = percent_encoding::percent_decode_str(source).decode_utf8_lossy();
+            let (bucket, key) = source.trim_start_matches('/').split_once('/').unwrap();
+            match state
+                .buckets
+                .get(bucket)
+                .and_then(|objects| objects.get(key))
+            {
+                Some(object) => object.clone(),
+                None => return error("404 Not Found", "NoSuchKey"),
+            }
+        }
+        None => stored_object(&request, request.body.clone()),
+    };
+    let etag = object.etag.clone();
+    let objects = state.buckets.entry(request.bucket).or_default();
+    objects.insert(request.key, object);
+    http_response("200 OK", &[("ETag", &etag)], "")
+}
+
+fn get(request: &MockRequest, state: &State) -> Vec<u8> {
+    let object = match state
+        .buckets
+        .get(&request.bucket)
+        .and_then(|objects| objects.get(&request.key))
+    {
+        Some(object) => object,
+        None if request.method == "HEAD" => {
+            return b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()
+        }
+        None => return error("404 Not Found", "NoSuchKey"),
+    };
+    if let Some(if_match) = request.headers.get("if-match") {
+        if *if_match != object.etag {
+            return error("412 Precondition Failed", "PreconditionFailed");
+        }
+    }
+
+    let size = object.content.len();
+    let range = request
+        .headers
+        .get("range")
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, end)| {
+            let start = start.parse::<usize>().ok()?;
+            let end = match end {
+                "" => size - 1,
+                end => end.parse::<usize>().ok()?.min(size - 1),
+            };
+            Some((start, end))
+        });
+    let (status, content, content_range) = match range {
+        Some((start, end)) if start <= end && end < size => (
+            "206 Partial Content",
+            &object.content[start..=end],
+            Some(format!("bytes {}-{}/{}", start, end, size)),
+        ),
+        _ => ("200 OK", &object.content[..], None),
+    };
+
+    let mut headers = vec![
+        ("ETag", object.etag.as_str()),
+        ("Content-Type", object.content_type.as_str()),
+        ("Last-Modified", LAST_MODIFIED),
+        ("Accept-Ranges", "bytes"),
+    ];
+    if let Some(content_range) = &content_range {
+        headers.push(("Content-Range", content_range));
+    }
+    for (name, value) in &object.metadata {
+        headers.push((name, value));
+    }
+
+    if request.method == "HEAD" {
+        // The length of the object, without a body
+        let mut response = format!("HTTP/1.1 {}\r\n", status);
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!("Content-Length: {}\r\n\r\n", content.len()));
+        return response.into_bytes();
+    }
+    http_response(status, &headers, content)
+}
+
+fn list(request: &MockRequest, state: &State) -> Vec<u8> {
+    let empty = BTreeMap::new();
+    let objects = state.buckets.get(&request.bucket).unwrap_or(&empty);
+    let param = |name: &str| request.query.get(name).map(String::as_str);
+    let v2 = param("list-type") == Some("2");
+    let prefix = param("prefix").unwrap_or("");
+    let delimiter = param("delimiter").filter(|delimiter| !delimiter.is_empty());
+    let max_keys = param("max-keys")
+        .and_then(|max_keys| max_keys.parse().ok())
+        .unwrap_or(1000);
+    // Listing resumes after this key, tokens being plain keys
+    let after = if v2 {
+        param("continuation-token").or_else(|| param("start-after"))
+    } else {
+        param("marker")
+    }
+    .unwrap_or("");
+
+    let common_prefix_of = |key: &str| {
+        let rest = key.strip_prefix(prefix)?;
+        let delimiter = delimiter?;
+        let end = rest.find(delimiter)? + delimiter.len();
+        Some(format!("{}{}", prefix, &rest[..end]))
+    };
+    // The page before ended with this common prefix, its keys are done with
+    let listed_prefix = common_prefix_of(after);
+
+    let mut contents = Vec::new();
+    let mut common_prefixes: Vec<String> = Vec::new();
+    let mut last = None;
+    let mut is_truncated = false;
+    for (key, object) in
+        objects.range::<str, _>((std::ops::Bound::Excluded(after), std::ops::Bound::Unbounded))
+    {
+        if !key.starts_with(prefix) {
+            continue;
+        }
+        let common_prefix = common_prefix_of(key);
+        if let Some(common_prefix) = &common_prefix {
+            if common_prefixes.last() == Some(common_prefix)
+                || listed_prefix.as_ref() == Some(common_prefix)
+            {
+                last = Some(key.clone());
+                continue;
+            }
+        }
+        if contents.len() + common_prefixes.len() == max_keys {
+            is_truncated = true;
+            break;
+        }
+        match common_prefix {
+            Some(common_prefix) => common_prefixes.push(common_prefix),
+            None => contents.push(format!(
+                "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag>\
+                 <Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                escape_xml(key),
+                LAST_MODIFIED_ISO,
+                escape_xml(&object.etag),
+                object.content.len()
+            )),
+        }
+        last = Some(key.clone());
+    }
+
+    let mut body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>{}</MaxKeys>\
+         <IsTruncated>{}</IsTruncated>",
+        escape_xml(&request.bucket),
+        escape_xml(prefix),
+        max_keys,
+        is_truncated
+    );
+    if let Some(delimiter) = delimiter {
+        body.push_str(&format!("<Delimiter>{}</Delimiter>", escape_xml(delimiter)));
+    }
+    if let (true, Some(last)) = (is_truncated, last) {
+        let tag = if v2 {
+            "NextContinuationToken"
+        } else {
+            "NextMarker"
+        };
+        body.push_str(&format!("<{tag}>{}</{tag}>", escape_xml(&last), tag = tag));
+    }
+    body.push_str(&contents.concat());
+    for common_prefix in common_prefixes {
+        body.push_str(&format!(
+            "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+            escape_xml(&common_prefix)
+        ));
+    }
+    body.push_str("</ListBucketResult>");
+    http_response("200 OK", &[("Content-Type", "application/xml")], body)
+}
+
+fn initiate_multipart_upload(request: MockRequest, state: &mut State) -> Vec<u8> {
+    state.next_upload_id += 1;
+    let upload_id = format!("upload-{}", state.next_upload_id);
+    let object = stored_object(&request, Vec::new());
+    state
+        .uploads
+        .insert(upload_id.clone(), (object, BTreeMap::new()));
+    http_response(
+        "200 OK",
+        &[("Content-Type", "application/xml")],
+        format!(
+            "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+             <UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+            escape_xml(&request.bucket),
+            escape_xml(&request.key),
+            upload_id
+        ),
+    )
+}
+
+fn upload_part(
+    request: &MockRequest,
+    state: &mut State,
+    upload_id: &str,
+    part_number: &str,
+) -> Vec<u8> {
+    let (part_number, (_, parts)) = match (part_number.parse(), state.uploads.get_mut(upload_id)) {
+        (Ok(part_number), Some(upload)) => (part_number, upload),
+        _ => return error("404 Not Found", "NoSuchUpload"),
+    };
+    parts.insert(part_number, request.body.clone());
+    http_response("200 OK", &[("ETag", &etag(&request.body))], "")
+}
+
+fn complete_multipart_upload(request: &MockRequest, state: &mut State, upload_id: &str) -> Vec<u8> {
+    let (mut object, parts) = match state.uploads.remove(upload_id) {
+        Some(upload) => upload,
+        None => return error("404 Not Found", "NoSuchUpload"),
+    };
+    let digests: Vec<u8> = parts
+        .values()
+        .flat_map(|part| md5::compute(part).0)
+        .collect();
+    object.etag = format!("\"{:x}-{}\"", md5::compute(digests), parts.len());
+    object.content = parts.into_values().flatten().collect();
+
+    let etag = object.etag.clone();
+    let objects = state.buckets.entry(request.bucket.clone()).or_default();
+    objects.insert(request.key.clone(), object);
+    http_response(
+        "200 OK",
+        &[("Content-Type", "application/xml")],
+        format!(
+            "<CompleteMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+             <ETag>{}</ETag></CompleteMultipartUploadResult>",
+            escape_xml(&request.bucket),
+            escape_xml(&request.key),
+            escape_xml(&etag)
+        ),
+    )
+}
@@ -1,14 +1,18 @@
+use std::convert::TryFrom;
 use std::str::FromStr;
 
 use crate::error::S3Error;
 use crate::request::ResponseData;
+use crate::Region;
 use crate::{bucket::CHUNK_SIZE, serde_types::HeadObjectResult};
 
 use crate::request::{AsyncRead, AsyncReadExt};
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use url::Url;
 
+#[derive(Debug)]
 pub struct PutStreamResponse {
     status_code: u16,
     uploaded_bytes: usize,
@@ -30,6 +34,53 @@ impl PutStreamResponse {
     }
 }
 
+/// Outcome of [`Bucket::head_object_if_modified_since`](crate::Bucket::head_object_if_modified_since).
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum HeadObjectModified {
+    /// The object changed since then, along with its current metadata
+    Modified(HeadObjectResult),
+    /// The object is as it was then, S3 answered `304 Not Modified`
+    NotModified,
+}
+
+/// Outcome of [`Bucket::delete_object`](crate::Bucket::delete_object).
+#[derive(Debug)]
+pub struct DeleteObjectOutput {
+    status_code: u16,
+    delete_marker: bool,
+    version_id: Option<String>,
+}
+
+impl DeleteObjectOutput {
+    pub(crate) fn from_response_data(response_data: &ResponseData) -> Self {
+        let headers = response_data.headers();
+        Self {
+            status_code: response_data.status_code(),
+            delete_marker: headers
+                .get("x-amz-delete-marker")
+                .is_some_and(|value| value == "true"),
+            version_id: headers.get("x-amz-version-id").cloned(),
+        }
+    }
+
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    /// Whether the delete created a delete marker, as it does in a versioned bucket when no
+    /// version is given, or removed one.
+    pub fn delete_marker(&self) -> bool {
+        self.delete_marker
+    }
+
+    /// Version of the object, or of the delete marker, that the delete applied to, in
+    /// versioned buckets.
+    pub fn version_id(&self) -> Option<&str> {
+        self.version_id.as_deref()
+    }
+}
+
 /// # Example
 /// ```rust,no_run
 /// use s3::utils::etag_for_path;
@@ -77,6 +128,209 @@ pub async fn read_chunk_async<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Ve
     Ok(chunk)
 }
 
+/// The key of the object at `path`. Methods taking the path of an object accept it with or
+/// without a leading slash, `/test.file` and `test.file` both being the key `test.file`. Only
+/// the first slash is dropped, so keys that do start with a slash are reached with two,
+/// `//test.file`.
+///
+/// # Example
+/// ```rust
+/// use s3::utils::object_key;
+///
+/// assert_eq!(object_key("/test.file"), "test.file");
+/// assert_eq!(object_key("test.file"), "test.file");
+/// assert_eq!(object_key("//test.file"), "/test.file");
+/// ```
+pub fn object_key(path: &str) -> &str {
+    path.strip_prefix('/').unwrap_or(path)
+}
+
+/// Bucket, key and region of an object, parsed from a URL with [`parse_s3_url`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedS3Location {
+    pub bucket: String,
+    /// Key of the object, without a leading slash, empty for the bucket itself
+    pub key: String,
+    /// Region named in the host of the URL, if any
+    pub region: Option<Region>,
+}
+
+impl TryFrom<&str> for ParsedS3Location {
+    type Error = S3Error;
+
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        let invalid = || S3Error::InvalidS3Url(url.to_string());
+
+        // Keys of s3:// URLs are taken as they are, query string and all, like the AWS CLI does
+        if let Some(location) = url.strip_prefix("s3://") {
+            let (bucket, key) = location.split_once('/').unwrap_or((location, ""));
+            if bucket.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(ParsedS3Location {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                region: None,
+            });
+        }
+
+        let parsed = Url::parse(url).map_err(|_| invalid())?;
+        if parsed.scheme() != "http" && parsed.scheme() != "https" {
+            return Err(invalid());
+        }
+        let host = parsed.host_str().ok_or_else(invalid)?;
+        let path = percent_encoding::percent_decode_str(parsed.path()).decode_utf8_lossy();
+        let path = path.strip_prefix('/').unwrap_or(&path);
+
+        let aws_host = host
+            .strip_suffix(".amazonaws.com")
+            .or_else(|| host.strip_suffix(".amazonaws.com.cn"));
+        let (bucket, key, region) = match aws_host {
+            Some(aws_host) => {
+                let labels = aws_host.split('.').collect::<Vec<_>>();
+                // The bucket name may contain dots, the endpoint labels are the last ones
+                let s3 = labels
+                    .iter()
+                    .rposition(|label| *label == "s3" || label.starts_with("s3-"))
+                    .ok_or_else(invalid)?;
+                let region = labels[s3]
+                    .strip_prefix("s3-")
+                    .into_iter()
+                    .chain(labels[s3 + 1..].iter().copied())
+                    .find(|label| !matches!(*label, "dualstack" | "accelerate"))
+                    .and_then(|region| match region {
+                        "external-1" => Some(Region::UsEast1),
+                        region => region.parse().ok(),
+                    });
+                if s3 == 0 {
+                    let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+                    (bucket.to_string(), key.to_string(), region)
+                } else {
+                    (labels[..s3].join("."), path.to_string(), region)
+                }
+            }
+            // Other hosts, e.g. MinIO, are taken to serve path style
+            None => {
+                let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+                (bucket.to_string(), key.to_string(), None)
+            }
+        };
+        if bucket.is_empty() {
+            return Err(invalid());
+        }
+        Ok(ParsedS3Location {
+            bucket,
+            key,
+            region,
+        })
+    }
+}
+
+/// Parse the URL of an object, or of a bucket, into its bucket, key and region, e.g. to take
+/// either from the command line.
+///
+/// Understands `s3://bucket/key`, virtual host style URLs like
+/// `https://bucket.s3.eu-west-1.amazonaws.com/key` and path style ones like
+/// `https://s3.eu-west-1.amazonaws.com/bucket/key`. The region is known only if the host names
+/// it, and URLs of hosts other than AWS are taken to be path style. Query strings of HTTP URLs
+/// are ignored.
+///
+/// # Example
+/// ```rust
+/// use s3::{parse_s3_url, Region};
+///
+/// let location = parse_s3_url("https://my-bucket.s3.eu-west-1.amazonaws.com/dir/test.file")?;
+/// assert_eq!(location.bucket, "my-bucket");
+/// assert_eq!(location.key, "dir/test.file");
+/// assert_eq!(location.region, Some(Region::EuWest1));
+/// # Ok::<(), s3::error::S3Error>(())
+/// ```
+pub fn parse_s3_url(url: &str) -> Result<ParsedS3Location, S3Error> {
+    ParsedS3Location::try_from(url)
+}
+
+/// Encode a user metadata value for its `x-amz-meta-*` header.
+///
+/// Header values are limited to printable ASCII, so values with other characters are sent as
+/// an RFC 2047 encoded-word, `=?UTF-8?B?<base64 of the UTF-8 bytes>?=`, the way S3 returns
+/// such metadata itself. Other values are sent as they are, unless they could be mistaken for
+/// an encoded-word. [`decode_metadata_value`] reverses this.
+///
+/// # Example
+/// ```rust
+/// use s3::utils::{decode_metadata_value, encode_metadata_value};
+///
+/// assert_eq!(encode_metadata_value("plain"), "plain");
+/// assert_eq!(encode_metadata_value("café"), "=?UTF-8?B?Y2Fmw6k=?=");
+/// assert_eq!(decode_metadata_value("=?UTF-8?B?Y2Fmw6k=?="), "café");
+/// ```
+pub fn encode_metadata_value(value: &str) -> String {
+    use base64::engine::general_purpose;
+    use base64::Engine;
+
+    let printable = value.bytes().all(|b| b == b' ' || b.is_ascii_graphic());
+    let looks_encoded = value.starts_with("=?") && value.ends_with("?=");
+    if printable && !looks_encoded {
+        return value.to_string();
+    }
+    format!(
+        "=?UTF-8?B?{}?=",
+        general_purpose::STANDARD.encode(value.as_bytes())
+    )
+}
+
+/// Decode a user metadata value read from its `x-amz-meta-*` header, made up of RFC 2047
+/// encoded-words in `UTF-8` with either the `B` or `Q` encoding. Values that aren't, or fail
+/// to decode, are returned as they are.
+pub fn decode_metadata_value(value: &str) -> String {
+    let mut decoded = Vec::new();
+    // Whitespace between adjacent encoded-words is not part of the value
+    for word in value.split_whitespace() {
+        match decode_encoded_word(word) {
+            Some(bytes) => decoded.extend(bytes),
+            None => return value.to_string(),
+        }
+    }
+    if decoded.is_empty() {
+        return value.to_string();
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_string())
+}
+
+/// Bytes of an RFC 2047 encoded-word, `=?<charset>?<encoding>?<text>?=`.
+fn decode_encoded_word(word: &str) -> Option<Vec<u8>> {
+    use base64::engine::general_purpose;
+    use base64::Engine;
+
+    let word = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut fields = word.splitn(3, '?');
+    let charset = fields.next()?;
+    let encoding = fields.next()?;
+    let text = fields.next()?;
+    if !charset.eq_ignore_ascii_case("utf-8") && !charset.eq_ignore_ascii_case("us-ascii") {
+        return None;
+    }
+    match encoding {
+        "B" | "b" => general_purpose::STANDARD.decode(text).ok(),
+        "Q" | "q" => {
+            let mut bytes = Vec::with_capacity(text.len());
+            let mut text = text.bytes();
+            while let Some(b) = text.next() {
+                match b {
+                    b'_' => bytes.push(b' '),
+                    b'=' => {
+                        let hex = [text.next()?, text.next()?];
+                        bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                    }
+                    b => bytes.push(b),
+                }
+            }
+            Some(bytes)
+        }
+        _ => None,
+    }
+}
+
 pub trait GetAndConvertHeaders {
     fn get_and_convert<T: FromStr>(&self, header: &str) -> Option<T>;
     fn get_string(&self, header: &str) -> Option<String>;
@@ -111,10 +365,11 @@ impl From<&http::HeaderMap> for HeadObjectResult {
         let mut values = ::std::collections::HashMap::new();
         for (key, value) in headers.iter() {
             if key.as_str().starts_with("x-amz-meta-") {
-                if let Ok(value) = value.to_str() {
+                // Some servers send non-ASCII values as raw UTF-8 rather than encoded
+                if let Ok(value) = std::str::from_utf8(value.as_bytes()) {
                     values.insert(
                         key.as_str()["x-amz-meta-".len()..].to_owned(),
-                        value.to_owned(),
+                        decode_metadata_value(value),
                     );
                 }
             }
@@ -138,25 +393,139 @@ impl From<&http::HeaderMap> for HeadObjectResult {
         result.storage_class = headers.get_string("x-amz-storage-class");
         result.version_id = headers.get_string("x-amz-version-id");
         result.website_redirect_location = headers.get_string("x-amz-website-redirect-location");
+        result.request_id = headers.get_string("x-amz-request-id");
+        result.extended_request_id = headers.get_string("x-amz-id-2");
         result
     }
 }
 
+/// Turn a `412 Precondition Failed` answer to a conditional request into
+/// [`S3Error::PreconditionFailed`], whether or not `fail-on-err` already made it an error.
+pub(crate) fn precondition_failed(
+    result: Result<ResponseData, S3Error>,
+) -> Result<ResponseData, S3Error> {
+    match result {
+        Ok(response_data) if response_data.status_code() == 412 => {
+            Err(S3Error::PreconditionFailed {
+                etag: response_data.header("etag").map(str::to_string),
+                request_id: response_data.request_id().map(str::to_string),
+                extended_request_id: response_data.extended_request_id().map(str::to_string),
+            })
+        }
+        Err(error) if error.status_code() == Some(412) => Err(S3Error::PreconditionFailed {
+            etag: error
+                .headers()
+                .and_then(|headers| headers.get_string("etag")),
+            request_id: error.request_id().map(str::to_string),
+            extended_request_id: error.extended_request_id().map(str::to_string),
+        }),
+        result => result,
+    }
+}
+
+/// Turn the `AccessControlListNotSupported` failure of an ACL operation into
+/// [`S3Error::AclNotSupported`], whether it comes as a response or, with `fail-on-err`, as an
+/// error.
+pub(crate) fn acl_not_supported(
+    result: Result<ResponseData, S3Error>,
+) -> Result<ResponseData, S3Error> {
+    let is_acl_not_supported =
+        |error: &S3Error| error.code().as_deref() == Some("AccessControlListNotSupported");
+    match result {
+        Ok(response_data) if response_data.status_code() == 400 => {
+            let error = S3Error::HttpFailWithBody(
+                400,
+                response_data.as_str_lossy().into_owned(),
+                Box::default(),
+            );
+            if is_acl_not_supported(&error) {
+                Err(S3Error::AclNotSupported {
+                    request_id: response_data.request_id().map(str::to_string),
+                    extended_request_id: response_data.extended_request_id().map(str::to_string),
+                })
+            } else {
+                Ok(response_data)
+            }
+        }
+        Err(error) if is_acl_not_supported(&error) => Err(S3Error::AclNotSupported {
+            request_id: error.request_id().map(str::to_string),
+            extended_request_id: error.extended_request_id().map(str::to_string),
+        }),
+        result => result,
+    }
+}
+
+/// Parse the first and last byte positions out of a `Content-Range` header value, e.g.
+/// `bytes 0-9/100`.
+pub(crate) fn parse_content_range(content_range: &str) -> Option<(u64, u64)> {
+    let range = content_range.strip_prefix("bytes ")?.split('/').next()?;
+    let (first, last) = range.split_once('-')?;
+    Some((first.trim().parse().ok()?, last.trim().parse().ok()?))
+}
+
 pub(crate) fn error_from_response_data(response_data: ResponseData) -> Result<S3Error, S3Error> {
-    let utf8_content = String::from_utf8(response_data.as_slice().to_vec())?;
+    let utf8_content = response_data.as_str_lossy().into_owned();
+    let mut headers = http::HeaderMap::new();
+    for (key, value) in response_data.headers() {
+        if let (Ok(key), Ok(value)) = (
+            http::HeaderName::from_str(&key),
+            http::HeaderValue::from_str(&value),
+        ) {
+            headers.insert(key, value);
+        }
+    }
     Err(S3Error::HttpFailWithBody(
         response_data.status_code(),
         utf8_content,
+        Box::new(headers),
     ))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utils::etag_for_path;
+    use crate::error::S3Error;
+    use crate::request::ResponseData;
+    use crate::utils::{
+        decode_metadata_value, encode_metadata_value, error_from_response_data, etag_for_path,
+        parse_s3_url, precondition_failed, ParsedS3Location,
+    };
+    use crate::Region;
+    use std::collections::HashMap;
     use std::fs::File;
     use std::io::prelude::*;
     use std::io::Cursor;
 
+    #[test]
+    fn test_metadata_value_encoding() {
+        assert_eq!(encode_metadata_value("just ascii"), "just ascii");
+        assert_eq!(encode_metadata_value("Zoë"), "=?UTF-8?B?Wm/Dqw==?=");
+        assert_eq!(
+            encode_metadata_value("line\nbreak"),
+            "=?UTF-8?B?bGluZQpicmVhaw==?="
+        );
+        // Taken for an encoded-word on the way back otherwise
+        assert_eq!(
+            decode_metadata_value(&encode_metadata_value("=?UTF-8?B?Wm/Dqw==?=")),
+            "=?UTF-8?B?Wm/Dqw==?="
+        );
+
+        assert_eq!(decode_metadata_value("=?utf-8?b?Wm/Dqw==?="), "Zoë");
+        assert_eq!(decode_metadata_value("=?UTF-8?Q?Zo=C3=AB_?="), "Zoë ");
+        assert_eq!(
+            decode_metadata_value("=?UTF-8?B?8J+Ymg==?= =?UTF-8?Q?_ok?="),
+            "😚 ok"
+        );
+        assert_eq!(decode_metadata_value("just ascii"), "just ascii");
+        assert_eq!(
+            decode_metadata_value("=?ISO-8859-1?Q?Zo=EB?="),
+            "=?ISO-8859-1?Q?Zo=EB?="
+        );
+        assert_eq!(
+            decode_metadata_value("=?UTF-8?B?not base64?="),
+            "=?UTF-8?B?not base64?="
+        );
+    }
+
     fn object(size: u32) -> Vec<u8> {
         (0..size).map(|_| 33).collect()
     }
@@ -214,4 +583,150 @@ mod test {
         let result = super::read_chunk(&mut blob).unwrap();
         assert_eq!(result.len(), 1_611_392);
     }
+
+    #[test]
+    fn test_error_from_response_data_keeps_headers() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "x-amz-request-id".to_string(),
+            "4442587FB7D0A2F9".to_string(),
+        );
+        headers.insert(
+            "x-amz-id-2".to_string(),
+            "vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=".to_string(),
+        );
+        headers.insert("retry-after".to_string(), "2".to_string());
+        let response_data =
+            ResponseData::new("<Error><Code>SlowDown</Code></Error>".into(), 503, headers);
+
+        let error = error_from_response_data(response_data).unwrap_err();
+
+        assert_eq!(error.status_code(), Some(503));
+        assert_eq!(error.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(
+            error.extended_request_id(),
+            Some("vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=")
+        );
+        assert_eq!(error.headers().unwrap().get("Retry-After").unwrap(), "2");
+        assert!(error.to_string().contains("SlowDown"));
+    }
+
+    #[test]
+    fn test_request_ids() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "x-amz-request-id".to_string(),
+            "4442587FB7D0A2F9".to_string(),
+        );
+        headers.insert(
+            "x-amz-id-2".to_string(),
+            "vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=".to_string(),
+        );
+        let response_data = ResponseData::new(
+            "<Error><Code>PreconditionFailed</Code></Error>".into(),
+            412,
+            headers,
+        );
+        assert_eq!(response_data.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(
+            response_data.extended_request_id(),
+            Some("vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=")
+        );
+
+        let error = precondition_failed(Ok(response_data)).unwrap_err();
+        match &error {
+            S3Error::PreconditionFailed {
+                request_id,
+                extended_request_id,
+                ..
+            } => {
+                assert_eq!(request_id.as_deref(), Some("4442587FB7D0A2F9"));
+                assert_eq!(
+                    extended_request_id.as_deref(),
+                    Some("vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=")
+                );
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert_eq!(error.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(
+            error.extended_request_id(),
+            Some("vlR7PnpV2Ce81puvwZkmqjmbTnmcfmHBzO4Ucbh+1/4=")
+        );
+    }
+
+    #[test]
+    fn test_parse_s3_url() {
+        let location = |bucket: &str, key: &str, region: Option<Region>| ParsedS3Location {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            region,
+        };
+
+        assert_eq!(
+            parse_s3_url("s3://my-bucket/dir/sub/test.file").unwrap(),
+            location("my-bucket", "dir/sub/test.file", None)
+        );
+        assert_eq!(
+            parse_s3_url("s3://my-bucket").unwrap(),
+            location("my-bucket", "", None)
+        );
+        assert_eq!(
+            parse_s3_url("s3://my-bucket/what?.txt").unwrap(),
+            location("my-bucket", "what?.txt", None)
+        );
+        assert_eq!(
+            parse_s3_url(
+                "https://my-bucket.s3.eu-west-1.amazonaws.com/dir/test%20file.txt?versionId=1"
+            )
+            .unwrap(),
+            location("my-bucket", "dir/test file.txt", Some(Region::EuWest1))
+        );
+        assert_eq!(
+            parse_s3_url("https://my.dotted.bucket.s3-eu-west-1.amazonaws.com/test.file").unwrap(),
+            location("my.dotted.bucket", "test.file", Some(Region::EuWest1))
+        );
+        assert_eq!(
+            parse_s3_url("https://my-bucket.s3.amazonaws.com/test.file").unwrap(),
+            location("my-bucket", "test.file", None)
+        );
+        assert_eq!(
+            parse_s3_url("https://my-bucket.s3.dualstack.us-west-2.amazonaws.com/test.file")
+                .unwrap(),
+            location("my-bucket", "test.file", Some(Region::UsWest2))
+        );
+        assert_eq!(
+            parse_s3_url(
+                "https://s3.eu-central-1.amazonaws.com/my-bucket/dir/sub/test.file?x-id=GetObject"
+            )
+            .unwrap(),
+            location("my-bucket", "dir/sub/test.file", Some(Region::EuCentral1))
+        );
+        assert_eq!(
+            parse_s3_url("https://s3.cn-north-1.amazonaws.com.cn/my-bucket").unwrap(),
+            location("my-bucket", "", Some(Region::CnNorth1))
+        );
+        assert_eq!(
+            parse_s3_url("http://localhost:9000/my-bucket/test.file").unwrap(),
+            location("my-bucket", "test.file", None)
+        );
+
+        for url in [
+            "s3://",
+            "s3:///test.file",
+            "ftp://my-bucket/test.file",
+            "https://s3.eu-west-1.amazonaws.com/",
+            "https://ec2.amazonaws.com/my-bucket",
+            "my-bucket/test.file",
+        ] {
+            assert!(
+                matches!(
+                    parse_s3_url(url),
+                    Err(crate::error::S3Error::InvalidS3Url(_))
+                ),
+                "{}",
+                url
+            );
+        }
+    }
 }